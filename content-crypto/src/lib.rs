@@ -0,0 +1,188 @@
+//! Off-chain side of the encrypted content scheme of the copyright contract.
+//!
+//! - The author encrypts the content with a random content key (ChaCha20-Poly1305), uploads the ciphertext
+//!   and registers the work with a `ContentRef` to it and the sha256 of the plaintext (`encrypt_content`).
+//! - A buyer creates an X25519 key pair and passes the public key to `get_access` (`KeyPair::generate`).
+//! - The author or the delivery agent wraps the content key for that public key and posts it with
+//!   `deliver_content_key` (`wrap_key`).
+//! - The buyer unwraps the content key and decrypts the content (`unwrap_key`, `decrypt_content`).
+//!
+//! Ciphertexts and wrapped keys are `nonce | ciphertext` and `ephemeral public key | ciphertext`. The
+//! contract reads public and wrapped keys as `Base64VecU8`.
+
+use std::fmt;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+/// Length of content keys and X25519 keys.
+pub const KEY_LEN: usize = 32;
+
+const NONCE_LEN: usize = 12;
+const WRAP_INFO: &[u8] = b"copyright content key";
+
+/// Errors returned when a ciphertext or a wrapped key cannot be opened.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+  /// The input is too short to hold a nonce or an ephemeral key.
+  Truncated,
+  /// The input was not encrypted for this key or has been tampered with.
+  Decryption,
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::Truncated => f.write_str("ciphertext is too short"),
+      Error::Decryption => f.write_str("cannot decrypt with this key"),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+/// Symmetric key of the content of one work.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ContentKey(pub [u8; KEY_LEN]);
+
+impl ContentKey {
+  pub fn generate() -> Self {
+    let mut key = [0u8; KEY_LEN];
+    OsRng.fill_bytes(&mut key);
+    Self(key)
+  }
+}
+
+/// X25519 key pair of a buyer. Only `public` goes on chain.
+pub struct KeyPair {
+  pub secret: [u8; KEY_LEN],
+  pub public: [u8; KEY_LEN],
+}
+
+impl KeyPair {
+  pub fn generate() -> Self {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let public = PublicKey::from(&secret);
+    Self { secret: secret.to_bytes(), public: public.to_bytes() }
+  }
+}
+
+/// An encrypted content and the hashes `create_work` needs.
+pub struct EncryptedContent {
+  /// `nonce | ciphertext`, the bytes to upload.
+  pub ciphertext: Vec<u8>,
+  /// Base58 sha256 of `ciphertext`, the `hash` of the `ContentRef`.
+  pub ciphertext_hash: String,
+  /// Base58 sha256 of the plaintext, the `content_hash` of the work.
+  pub content_hash: String,
+}
+
+/// sha256 of the plaintext, the hash the contract registers.
+pub fn hash_content(plaintext: &[u8]) -> [u8; 32] {
+  Sha256::digest(plaintext).into()
+}
+
+/// Encrypt a content with a fresh nonce.
+pub fn encrypt_content(key: &ContentKey, plaintext: &[u8]) -> EncryptedContent {
+  let cipher = ChaCha20Poly1305::new(Key::from_slice(&key.0));
+  let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+  let mut sealed = nonce.to_vec();
+  sealed.extend(cipher.encrypt(&nonce, plaintext).expect("Encryption cannot fail"));
+
+  EncryptedContent {
+    ciphertext_hash: bs58::encode(hash_content(&sealed)).into_string(),
+    content_hash: bs58::encode(hash_content(plaintext)).into_string(),
+    ciphertext: sealed,
+  }
+}
+
+/// Decrypt the content downloaded from the `ContentRef` of a work.
+pub fn decrypt_content(key: &ContentKey, sealed: &[u8]) -> Result<Vec<u8>, Error> {
+  if sealed.len() < NONCE_LEN {
+    return Err(Error::Truncated);
+  }
+
+  let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+  ChaCha20Poly1305::new(Key::from_slice(&key.0))
+    .decrypt(Nonce::from_slice(nonce), ciphertext)
+    .map_err(|_| Error::Decryption)
+}
+
+/// Wrap a content key for the public key a buyer passed to `get_access`.
+pub fn wrap_key(key: &ContentKey, recipient: &[u8; KEY_LEN]) -> Vec<u8> {
+  let ephemeral = EphemeralSecret::random_from_rng(OsRng);
+  let ephemeral_public = PublicKey::from(&ephemeral);
+  let shared = ephemeral.diffie_hellman(&PublicKey::from(*recipient));
+
+  let cipher = wrapping_cipher(shared.as_bytes(), ephemeral_public.as_bytes(), recipient);
+  let mut wrapped = ephemeral_public.to_bytes().to_vec();
+  wrapped.extend(cipher.encrypt(&Nonce::default(), key.0.as_slice()).expect("Encryption cannot fail"));
+  wrapped
+}
+
+/// Open a wrapped key with the secret key of the buyer.
+pub fn unwrap_key(wrapped: &[u8], secret: &[u8; KEY_LEN]) -> Result<ContentKey, Error> {
+  if wrapped.len() < KEY_LEN {
+    return Err(Error::Truncated);
+  }
+
+  let secret = StaticSecret::from(*secret);
+  let recipient = PublicKey::from(&secret);
+  let (ephemeral_public, ciphertext) = wrapped.split_at(KEY_LEN);
+  let ephemeral_public: [u8; KEY_LEN] = ephemeral_public.try_into().unwrap();
+  let shared = secret.diffie_hellman(&PublicKey::from(ephemeral_public));
+
+  let cipher = wrapping_cipher(shared.as_bytes(), &ephemeral_public, recipient.as_bytes());
+  let key = cipher.decrypt(&Nonce::default(), ciphertext).map_err(|_| Error::Decryption)?;
+  Ok(ContentKey(key.try_into().map_err(|_| Error::Decryption)?))
+}
+
+/// Every wrapping uses a new ephemeral key, so the derived key is used once and the nonce can be zero.
+fn wrapping_cipher(shared: &[u8; 32], ephemeral_public: &[u8; KEY_LEN], recipient: &[u8; KEY_LEN]) -> ChaCha20Poly1305 {
+  let mut salt = ephemeral_public.to_vec();
+  salt.extend_from_slice(recipient);
+
+  let mut key = [0u8; KEY_LEN];
+  Hkdf::<Sha256>::new(Some(&salt), shared).expand(WRAP_INFO, &mut key).expect("32 bytes is a valid length");
+  ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn buyer_decrypts_with_delivered_key() {
+    let content_key = ContentKey::generate();
+    let encrypted = encrypt_content(&content_key, b"my song lyrics");
+    assert_eq!(encrypted.content_hash, bs58::encode(hash_content(b"my song lyrics")).into_string());
+
+    let buyer = KeyPair::generate();
+    let wrapped = wrap_key(&content_key, &buyer.public);
+
+    let unwrapped = unwrap_key(&wrapped, &buyer.secret).unwrap();
+    assert_eq!(unwrapped, content_key);
+    assert_eq!(decrypt_content(&unwrapped, &encrypted.ciphertext).unwrap(), b"my song lyrics");
+  }
+
+  #[test]
+  fn other_account_cannot_unwrap() {
+    let content_key = ContentKey::generate();
+    let buyer = KeyPair::generate();
+    let wrapped = wrap_key(&content_key, &buyer.public);
+
+    assert_eq!(unwrap_key(&wrapped, &KeyPair::generate().secret), Err(Error::Decryption));
+  }
+
+  #[test]
+  fn wrong_content_key_fails() {
+    let encrypted = encrypt_content(&ContentKey::generate(), b"content");
+
+    assert_eq!(decrypt_content(&ContentKey::generate(), &encrypted.ciphertext), Err(Error::Decryption));
+    assert_eq!(decrypt_content(&ContentKey::generate(), &[0; 4]), Err(Error::Truncated));
+  }
+}
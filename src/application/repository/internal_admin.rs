@@ -0,0 +1,119 @@
+use near_sdk::{borsh::BorshSerialize, collections::UnorderedSet};
+
+use crate::models::{
+  contract::{ContractStorageKey, ELearningContract},
+  user::{Roles, UserId},
+};
+
+use super::hash_account_id;
+
+impl ELearningContract {
+  /// The owner acts as an Admin even without a user account
+  pub(crate) fn internal_is_admin(&self, account_id: &UserId) -> bool {
+    if account_id == &self.owner_id {
+      return true;
+    }
+    match self.user_metadata_by_id.get(account_id) {
+      Some(user) => user.metadata.role == Roles::Admin && !user.metadata.suspended,
+      None => false,
+    }
+  }
+
+  pub(crate) fn internal_assert_admin(&self, account_id: &UserId) {
+    assert!(self.internal_is_admin(account_id), "Only an admin can do this");
+  }
+
+  pub(crate) fn internal_is_manager(&self, account_id: &UserId) -> bool {
+    match self.user_metadata_by_id.get(account_id) {
+      Some(user) => user.metadata.role == Roles::Manager && !user.metadata.suspended,
+      None => false,
+    }
+  }
+
+  /// Admins and managers review instructor applications
+  pub(crate) fn internal_assert_reviewer(&self, account_id: &UserId) {
+    assert!(
+      self.internal_is_admin(account_id) || self.internal_is_manager(account_id),
+      "Only an admin or a manager can do this"
+    );
+  }
+
+  /// Admins manage everyone but the admins, which only the owner manages. A manager manages the instructors
+  /// it granted the role to.
+  pub(crate) fn internal_assert_can_manage(&self, account_id: &UserId, user_id: &UserId) {
+    let user = self.user_metadata_by_id.get(user_id).expect("User does not exist");
+    if account_id == &self.owner_id {
+      return;
+    }
+    assert!(user_id != &self.owner_id, "Only the contract owner can manage itself");
+
+    if user.metadata.role == Roles::Admin {
+      assert!(account_id == &self.owner_id, "Only the contract owner can manage an admin");
+    } else if !self.internal_is_admin(account_id) {
+      assert!(
+        self.internal_is_manager(account_id) && user.metadata.managed_by.as_ref() == Some(account_id),
+        "You are not an admin or the manager of this user"
+      );
+    }
+  }
+
+  /// Change the role of a user and keep the instructor and manager indexes in sync
+  pub(crate) fn internal_set_role(&mut self, user_id: &UserId, role: Roles, manager_id: Option<UserId>) {
+    let mut user = self.user_metadata_by_id.get(user_id).expect("User does not exist");
+
+    if let Some(old_manager) = user.metadata.managed_by.take() {
+      self.internal_remove_instructor_from_manager(&old_manager, user_id);
+    }
+    if user.metadata.role == Roles::Manager && role != Roles::Manager {
+      self.internal_release_instructors(user_id);
+    }
+
+    if role == Roles::Instructor {
+      self.intructor_users.insert(user_id);
+      if let Some(manager_id) = &manager_id {
+        self.internal_add_instructor_to_manager(manager_id, user_id);
+      }
+      user.metadata.managed_by = manager_id;
+    } else {
+      self.intructor_users.remove(user_id);
+    }
+
+    user.metadata.role = role;
+    self.user_metadata_by_id.insert(user_id, &user);
+  }
+
+  pub(crate) fn internal_add_instructor_to_manager(&mut self, manager_id: &UserId, instructor_id: &UserId) {
+    let mut instructor_set = self.instructors_per_manager.get(manager_id).unwrap_or_else(|| {
+      UnorderedSet::new(
+        ContractStorageKey::InstructorsPerManagerInner { manager_id_hash: hash_account_id(manager_id) }
+          .try_to_vec()
+          .unwrap(),
+      )
+    });
+    instructor_set.insert(instructor_id);
+    self.instructors_per_manager.insert(manager_id, &instructor_set);
+  }
+
+  pub(crate) fn internal_remove_instructor_from_manager(&mut self, manager_id: &UserId, instructor_id: &UserId) {
+    if let Some(mut instructor_set) = self.instructors_per_manager.get(manager_id) {
+      instructor_set.remove(instructor_id);
+      if instructor_set.is_empty() {
+        self.instructors_per_manager.remove(manager_id);
+      } else {
+        self.instructors_per_manager.insert(manager_id, &instructor_set);
+      }
+    }
+  }
+
+  /// A manager that loses its role leaves its instructors without a manager. They stay instructors
+  fn internal_release_instructors(&mut self, manager_id: &UserId) {
+    if let Some(mut instructor_set) = self.instructors_per_manager.remove(manager_id) {
+      for instructor_id in instructor_set.iter() {
+        let mut instructor = self.user_metadata_by_id.get(&instructor_id).unwrap();
+        instructor.metadata.managed_by = None;
+        self.user_metadata_by_id.insert(&instructor_id, &instructor);
+      }
+      instructor_set.clear();
+    }
+  }
+}
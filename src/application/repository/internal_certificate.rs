@@ -1,9 +1,12 @@
-use near_sdk::{borsh::BorshSerialize, collections::UnorderedSet};
+use near_sdk::{borsh::BorshSerialize, collections::UnorderedSet, serde_json::json};
 
+use crate::event::{EventLog, EventLogVariant, NftTransferLog};
 use crate::models::{
-  certificate::CertificateId,
+  certificate::{CertificateId, CertificateMetadata},
   contract::{ContractStorageKey, ELearningContract},
   course::CourseId,
+  nft::{JsonToken, TokenMetadata},
+  skill::SkillMetadata,
   user::UserId,
 };
 
@@ -30,4 +33,129 @@ impl ELearningContract {
     new_user_data.certificate.push(certificate_id.to_string());
     self.user_metadata_by_id.insert(student_id, &new_user_data);
   }
+
+  pub(crate) fn internal_remove_certificate_from_user(&mut self, user_id: &UserId, certificate_id: &CertificateId) {
+    let mut certificate_set = self.certificate_per_user.get(user_id).expect("This user has no certificate");
+    certificate_set.remove(certificate_id);
+
+    if certificate_set.is_empty() {
+      self.certificate_per_user.remove(user_id);
+    } else {
+      self.certificate_per_user.insert(user_id, &certificate_set);
+    }
+
+    // remove certificate in user data
+    let mut user_data = self.user_metadata_by_id.get(user_id).unwrap();
+    user_data.certificate.retain(|id| id != certificate_id);
+    self.user_metadata_by_id.insert(user_id, &user_data);
+  }
+
+  /// Remove a certificate from the contract. If it was used to mint a skill, the skill credit is taken back
+  pub(crate) fn internal_burn_certificate(&mut self, certificate_id: &CertificateId) -> CertificateMetadata {
+    let certificate = self.certificate_metadata_by_id.remove(certificate_id).expect("This Certificate is not exist");
+    self.internal_remove_certificate_from_user(&certificate.student, certificate_id);
+    self.all_certificates.remove(certificate_id);
+
+    if certificate.certificate_used {
+      let skill = SkillMetadata {
+        skill_id: certificate.skill_id.clone(),
+        credit: certificate.credit,
+        credit_from: certificate_id.clone(),
+        use_skill: true,
+        description: certificate.description.clone(),
+      };
+      self.internal_remove_skill_metadata_from_skill_id(&certificate.skill_id, &skill);
+
+      let mut user = self.user_metadata_by_id.get(&certificate.student).unwrap();
+      let remaining =
+        user.skill.get(&certificate.skill_id).map_or(0, |credit| credit.saturating_sub(certificate.credit));
+      if remaining == 0 {
+        user.skill.remove(&certificate.skill_id);
+      } else {
+        user.skill.insert(certificate.skill_id.clone(), remaining);
+      }
+      self.user_metadata_by_id.insert(&certificate.student, &user);
+    }
+
+    certificate
+  }
+
+  /// Move a certificate to another user. Course rules are checked by the caller
+  pub(crate) fn internal_move_certificate(
+    &mut self,
+    sender_id: &UserId,
+    receiver_id: &UserId,
+    certificate_id: &CertificateId,
+    memo: Option<String>,
+  ) -> CertificateMetadata {
+    let mut certificate = self.certificate_metadata_by_id.get(certificate_id).expect("This Certificate is not exist");
+    assert!(&certificate.student == sender_id, "This certificate is not belong to you");
+    assert!(sender_id != receiver_id, "You can not transfer the certificate to yourself");
+    assert!(self.user_metadata_by_id.contains_key(receiver_id), "The receiver is not a user");
+
+    self.internal_remove_certificate_from_user(sender_id, certificate_id);
+    self.internal_add_certificate_to_user(receiver_id, certificate_id);
+
+    certificate.student = receiver_id.clone();
+    self.certificate_metadata_by_id.insert(certificate_id, &certificate);
+
+    EventLog::nft(EventLogVariant::NftTransfer(vec![NftTransferLog {
+      authorized_id: None,
+      old_owner_id: sender_id.clone(),
+      new_owner_id: receiver_id.clone(),
+      token_ids: vec![certificate_id.clone()],
+      memo,
+    }]))
+    .emit();
+
+    certificate
+  }
+
+  /// Transfer a certificate if its course allows it and no skill was minted from it, since the skill credit
+  /// stays with the student who minted it
+  pub(crate) fn internal_transfer_certificate(
+    &mut self,
+    sender_id: &UserId,
+    receiver_id: &UserId,
+    certificate_id: &CertificateId,
+    memo: Option<String>,
+  ) -> CertificateMetadata {
+    let certificate = self.certificate_metadata_by_id.get(certificate_id).expect("This Certificate is not exist");
+    let course = self.course_metadata_by_id.get(&certificate.course_id).expect("This course is not exist");
+    assert!(course.certificate_transferable, "Certificates of this course can not be transferred");
+    assert!(!certificate.certificate_used, "A certificate used for a skill can not be transferred");
+    self.internal_assert_not_suspended(sender_id);
+
+    self.internal_move_certificate(sender_id, receiver_id, certificate_id, memo)
+  }
+
+  /// NEP-171 view of a certificate
+  pub(crate) fn internal_json_token(&self, certificate_id: &CertificateId) -> Option<JsonToken> {
+    let certificate = self.certificate_metadata_by_id.get(certificate_id)?;
+    let title = self.course_metadata_by_id.get(&certificate.course_id).map(|course| course.title);
+    let extra = json!({
+      "course_id": certificate.course_id,
+      "skill_id": certificate.skill_id,
+      "credit": certificate.credit,
+    });
+
+    Some(JsonToken {
+      token_id: certificate.certificate_id,
+      owner_id: certificate.student,
+      metadata: TokenMetadata {
+        title,
+        description: certificate.description,
+        media: Some(certificate.media),
+        media_hash: None,
+        copies: Some(1),
+        issued_at: Some(certificate.issued_at.to_string()),
+        expires_at: None,
+        starts_at: None,
+        updated_at: None,
+        extra: Some(extra.to_string()),
+        reference: None,
+        reference_hash: None,
+      },
+    })
+  }
 }
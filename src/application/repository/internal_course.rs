@@ -1,12 +1,24 @@
-use near_sdk::{borsh::BorshSerialize, collections::UnorderedSet};
+use near_sdk::{
+  borsh::BorshSerialize,
+  collections::{UnorderedMap, UnorderedSet},
+  env,
+  json_types::U128,
+  AccountId, Balance, Gas,
+};
 
+use crate::application::service::impl_course::ext_self;
+use crate::event::{CoursePurchaseLog, EventLog, EventLogVariant};
+use crate::fungible_token;
 use crate::models::{
   contract::{ContractStorageKey, ELearningContract},
-  course::CourseId,
+  course::{CourseEscrow, CourseId, CourseMetadata},
+  pool::{PoolId, FULL_SHARE},
   user::UserId,
 };
 
-use super::hash_account_id;
+use super::{hash_account_id, hash_course_id};
+
+const GAS_FOR_RESOLVE_PAYOUT: Gas = Gas(10_000_000_000_000);
 
 impl ELearningContract {
   pub(crate) fn internal_add_course_to_instructor(&mut self, account_id: &UserId, course_id: &CourseId) {
@@ -23,4 +35,133 @@ impl ELearningContract {
 
     self.courses_per_instructor.insert(account_id, &courses_set);
   }
+
+  /// Split the price of a course with a platform fee in basis points. `pool_id` is credited with its share at
+  /// once. Returns what goes to the owner and to the instructor
+  pub(crate) fn internal_split_course_payment(
+    &mut self,
+    pool_id: &Option<PoolId>,
+    price: Balance,
+    platform_fee: u32,
+  ) -> (Balance, Balance) {
+    let platform_amount = price * Balance::from(platform_fee) / 10_000;
+    let mut instructor_amount = price - platform_amount;
+
+    if let Some(pool_id) = pool_id {
+      let sale_share = self.pool_metadata_by_id.get(pool_id).unwrap().sale_share;
+      let pool_amount = instructor_amount * Balance::from(sale_share) / Balance::from(FULL_SHARE);
+      self.internal_credit_pool(pool_id, pool_amount);
+      instructor_amount -= pool_amount;
+    }
+
+    (platform_amount, instructor_amount)
+  }
+
+  /// Send the owner and the instructor their part of one or more course payments, in `token_id` or NEAR. A part
+  /// whose transfer fails is credited to the payout balance of its receiver
+  pub(crate) fn internal_pay_out(
+    &self,
+    instructor_id: &UserId,
+    token_id: &Option<AccountId>,
+    platform_amount: Balance,
+    instructor_amount: Balance,
+  ) {
+    if platform_amount > 0 {
+      self.internal_send_payout(&self.owner_id, token_id, platform_amount);
+    }
+    if instructor_amount > 0 {
+      self.internal_send_payout(instructor_id, token_id, instructor_amount);
+    }
+  }
+
+  /// Transfer a payout. The callback credits it to the payout balance of `receiver_id` if the transfer fails
+  pub(crate) fn internal_send_payout(&self, receiver_id: &AccountId, token_id: &Option<AccountId>, amount: Balance) {
+    let resolve = ext_self::ext(env::current_account_id()).with_static_gas(GAS_FOR_RESOLVE_PAYOUT).resolve_payout(
+      receiver_id.clone(),
+      token_id.clone(),
+      U128(amount),
+    );
+    fungible_token::transfer(token_id, receiver_id, amount).then(resolve);
+  }
+
+  pub(crate) fn internal_credit_payout(
+    &mut self,
+    receiver_id: &AccountId,
+    token_id: &Option<AccountId>,
+    amount: Balance,
+  ) {
+    let key = (receiver_id.clone(), token_id.clone());
+    let balance = self.payout_balances.get(&key).unwrap_or(0);
+    self.payout_balances.insert(&key, &(balance + amount));
+  }
+
+  /// Enroll a student in a course the student paid the price of, in the price token of the course. The payment
+  /// is held until the refund period ends, or the platform, the pool and the instructor are paid at once
+  pub(crate) fn internal_enroll(&mut self, user_id: UserId, mut course: CourseMetadata) {
+    let course_id = course.course_id.clone();
+    assert!(!course.delisted, "This course is delisted");
+    assert!(self.user_metadata_by_id.contains_key(&user_id), "You need registration to use platform!");
+    self.internal_assert_not_suspended(&user_id);
+    assert!(user_id != course.instructor_id, "You own the course");
+    assert!(!course.students_studying_map.contains_key(&user_id), "You already have this course!");
+
+    // Plus 1 student to course owner
+    let mut coure_owner = self.user_metadata_by_id.get(&course.instructor_id).unwrap();
+    coure_owner.metadata.students += 1;
+    self.user_metadata_by_id.insert(&coure_owner.user_id, &coure_owner);
+
+    // Storage new course data
+    course.students_studying_map.insert(user_id.clone(), env::block_timestamp_ms());
+    let mut user = self.user_metadata_by_id.get(&user_id).unwrap();
+    user.courses.push(course_id.clone());
+    self.user_metadata_by_id.insert(&user_id, &user);
+    self.course_metadata_by_id.insert(&course_id, &course);
+
+    // Hold the payment until the refund period ends, or pay the platform, the pool and the instructor at once
+    if course.refund_period > 0 && course.price.0 > 0 {
+      let escrow = CourseEscrow {
+        student_id: user_id.clone(),
+        amount: course.price,
+        token_id: course.price_token.clone(),
+        platform_fee: self.platform_fee,
+        pool_id: course.pool_id.clone(),
+        release_at: env::block_timestamp_ms() + course.refund_period,
+      };
+      self.internal_add_escrow(&course_id, &escrow);
+    } else {
+      let (platform_amount, instructor_amount) =
+        self.internal_split_course_payment(&course.pool_id, course.price.0, self.platform_fee);
+      self.internal_pay_out(&course.instructor_id, &course.price_token, platform_amount, instructor_amount);
+    }
+
+    EventLog::elearning(EventLogVariant::CoursePurchase(vec![CoursePurchaseLog {
+      course_id,
+      student_id: user_id,
+      amount: course.price,
+      token_id: course.price_token,
+    }]))
+    .emit();
+  }
+
+  pub(crate) fn internal_add_escrow(&mut self, course_id: &CourseId, escrow: &CourseEscrow) {
+    let mut escrow_map = self.escrows_per_course.get(course_id).unwrap_or_else(|| {
+      UnorderedMap::new(
+        ContractStorageKey::EscrowsPerCourseInner { course_id_hash: hash_course_id(course_id) }.try_to_vec().unwrap(),
+      )
+    });
+    escrow_map.insert(&escrow.student_id, escrow);
+    self.escrows_per_course.insert(course_id, &escrow_map);
+  }
+
+  /// Remove the escrow of a student from a course and return it
+  pub(crate) fn internal_remove_escrow(&mut self, course_id: &CourseId, student_id: &UserId) -> Option<CourseEscrow> {
+    let mut escrow_map = self.escrows_per_course.get(course_id)?;
+    let escrow = escrow_map.remove(student_id)?;
+    if escrow_map.is_empty() {
+      self.escrows_per_course.remove(course_id);
+    } else {
+      self.escrows_per_course.insert(course_id, &escrow_map);
+    }
+    Some(escrow)
+  }
 }
@@ -0,0 +1,70 @@
+use near_sdk::env;
+
+use crate::event::{EventLog, EventLogVariant, InstructorApplicationLog};
+use crate::models::{
+  contract::ELearningContract,
+  instructor::{
+    ApplicationStatus, InstructorApplication, MAX_APPLICATION_CATEGORIES, MAX_CATEGORY_LEN, MAX_PORTFOLIO_LEN,
+  },
+  user::{Roles, UserId},
+};
+
+impl ELearningContract {
+  /// Close a pending application. An approved applicant becomes an Instructor, managed by the reviewer if the
+  /// reviewer is a manager
+  pub(crate) fn internal_review_application(
+    &mut self,
+    reviewer_id: UserId,
+    user_id: &UserId,
+    status: ApplicationStatus,
+    reason: Option<String>,
+  ) -> InstructorApplication {
+    self.internal_assert_reviewer(&reviewer_id);
+    assert!(&reviewer_id != user_id, "You can not review your own application");
+
+    let mut application = self.instructor_applications.get(user_id).expect("This user has no application");
+    assert!(application.status == ApplicationStatus::Pending, "This application is not pending");
+
+    if status == ApplicationStatus::Approved {
+      // The role or the suspension of the applicant may have changed since the application
+      let applicant = self.user_metadata_by_id.get(user_id).expect("This user is not exist");
+      assert!(applicant.metadata.role == Roles::Subscriber, "Only a Subscriber can become an instructor");
+      assert!(!applicant.metadata.suspended, "This user is suspended");
+      let manager_id = if self.internal_is_admin(&reviewer_id) { None } else { Some(reviewer_id.clone()) };
+      self.internal_set_role(user_id, Roles::Instructor, manager_id);
+    }
+
+    application.status = status;
+    application.reviewer_id = Some(reviewer_id.clone());
+    application.reason = reason.clone();
+    application.reviewed_at = Some(env::block_timestamp_ms());
+    self.instructor_applications.insert(user_id, &application);
+    self.pending_applications.remove(user_id);
+
+    EventLog::elearning(EventLogVariant::InstructorApplication(vec![InstructorApplicationLog {
+      user_id: user_id.clone(),
+      status: format!("{:?}", status),
+      reviewer_id: Some(reviewer_id),
+      reason,
+    }]))
+    .emit();
+
+    application
+  }
+}
+
+/// Panic unless the portfolio and the categories of an application are within the limits
+pub(crate) fn assert_valid_application(portfolio: &Option<String>, categories: &[String]) {
+  if let Some(portfolio) = portfolio {
+    assert!(portfolio.len() <= MAX_PORTFOLIO_LEN, "Portfolio link is too long");
+  }
+  assert!(!categories.is_empty(), "Request at least one category");
+  assert!(categories.len() <= MAX_APPLICATION_CATEGORIES, "Request at most {} categories", MAX_APPLICATION_CATEGORIES);
+  for category in categories {
+    assert!(
+      !category.trim().is_empty() && category.len() <= MAX_CATEGORY_LEN,
+      "Category names must have 1 to {} characters",
+      MAX_CATEGORY_LEN
+    );
+  }
+}
@@ -0,0 +1,38 @@
+use near_sdk::{borsh::BorshSerialize, collections::UnorderedSet, json_types::U128, Balance};
+
+use crate::models::{
+  contract::{ContractStorageKey, ELearningContract},
+  pool::{PoolId, FULL_SHARE},
+  user::UserId,
+};
+
+use super::hash_account_id;
+
+impl ELearningContract {
+  pub(crate) fn internal_add_pool_to_user(&mut self, user_id: &UserId, pool_id: &PoolId) {
+    let mut pool_set = self.pools_per_user.get(user_id).unwrap_or_else(|| {
+      UnorderedSet::new(
+        ContractStorageKey::PoolsPerUserInner { account_id_hash: hash_account_id(user_id) }.try_to_vec().unwrap(),
+      )
+    });
+    pool_set.insert(pool_id);
+    self.pools_per_user.insert(user_id, &pool_set);
+  }
+
+  /// Split `amount` between the members of a pool by their shares. The rounding remainder goes to the first
+  /// member, so the balances always add up to what the pool received
+  pub(crate) fn internal_credit_pool(&mut self, pool_id: &PoolId, amount: Balance) {
+    let mut pool = self.pool_metadata_by_id.get(pool_id).expect("This pool is not exist");
+
+    let mut credited = 0;
+    for member in pool.members.iter_mut() {
+      let member_amount = amount * Balance::from(member.share) / Balance::from(FULL_SHARE);
+      member.balance = U128(member.balance.0 + member_amount);
+      credited += member_amount;
+    }
+    pool.members[0].balance = U128(pool.members[0].balance.0 + amount - credited);
+
+    pool.total_received = U128(pool.total_received.0 + amount);
+    self.pool_metadata_by_id.insert(pool_id, &pool);
+  }
+}
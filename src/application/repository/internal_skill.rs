@@ -22,4 +22,15 @@ impl ELearningContract {
 
     self.skill_metadata_by_skill_id.insert(skill_id, &skill_set);
   }
+
+  pub(crate) fn internal_remove_skill_metadata_from_skill_id(
+    &mut self,
+    skill_id: &SkillId,
+    skill_metadata: &SkillMetadata,
+  ) {
+    if let Some(mut skill_set) = self.skill_metadata_by_skill_id.get(skill_id) {
+      skill_set.remove(skill_metadata);
+      self.skill_metadata_by_skill_id.insert(skill_id, &skill_set);
+    }
+  }
 }
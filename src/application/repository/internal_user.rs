@@ -1 +1,10 @@
+use crate::models::{contract::ELearningContract, user::UserId};
 
+impl ELearningContract {
+  /// Panic if the user is suspended. Accounts without a user are not checked
+  pub(crate) fn internal_assert_not_suspended(&self, user_id: &UserId) {
+    if let Some(user) = self.user_metadata_by_id.get(user_id) {
+      assert!(!user.metadata.suspended, "Your account is suspended");
+    }
+  }
+}
@@ -5,8 +5,11 @@ use unidecode::unidecode;
 
 use crate::models::{course::CourseId, skill::SkillId, user::UserId};
 
+pub mod internal_admin;
 pub mod internal_certificate;
 pub mod internal_course;
+pub mod internal_instructor;
+pub mod internal_pool;
 pub mod internal_skill;
 pub mod internal_user;
 
@@ -23,6 +26,13 @@ pub(crate) fn convert_coure_title_to_cousrse_id(title: &str, account_id: String)
   result.replace(' ', "_")
 }
 
+//used to generate a unique prefix in our storage collections (this is to avoid data collisions)
+pub(crate) fn hash_course_id(course_id: &CourseId) -> CryptoHash {
+  let mut hash = CryptoHash::default();
+  hash.copy_from_slice(&env::sha256(course_id.as_bytes()));
+  hash
+}
+
 //used to generate a unique prefix in our storage collections (this is to avoid data collisions)
 pub(crate) fn hash_account_id(account_id: &AccountId) -> CryptoHash {
   //get the default hash
@@ -59,6 +69,18 @@ pub(crate) fn hash_skill_id(skill_id: &SkillId) -> CryptoHash {
 
 /*
 
+Function for pool
+
+*/
+pub(crate) fn convert_to_pool_id(name: &str, creator_id: &UserId) -> String {
+  let unaccented = unidecode(name);
+  let lowercased = unaccented.to_ascii_lowercase();
+  let result = "pool ".to_string() + &lowercased + " " + &creator_id.to_string().to_ascii_lowercase();
+  result.replace(' ', "_")
+}
+
+/*
+
 Function for certificate
 
 */
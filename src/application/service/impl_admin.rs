@@ -0,0 +1,315 @@
+use crate::event::{CourseDelistLog, EventLog, EventLogVariant, NftBurnLog, RoleUpdateLog, UserSuspendLog};
+use crate::models::{
+  admin::{AdminFeatures, MAX_PLATFORM_FEE},
+  certificate::CertificateId,
+  contract::{ELearningContract, ELearningContractExt},
+  course::CourseId,
+  user::{JsonUser, Roles, UserId},
+};
+use near_sdk::{env, near_bindgen, AccountId};
+
+#[near_bindgen]
+/// Implement function for admins and managers
+impl AdminFeatures for ELearningContract {
+  #[payable]
+  fn grant_role(&mut self, user_id: UserId, role: Roles) -> JsonUser {
+    let initial_storage = env::storage_usage();
+    let caller = env::predecessor_account_id();
+    let user = self.user_metadata_by_id.get(&user_id).expect("User does not exist");
+    assert!(user.metadata.role != role, "This user already has this role");
+
+    match role {
+      Roles::Subscriber => panic!("Use revoke_role to make a user a Subscriber"),
+      Roles::Admin => {
+        assert!(caller == self.owner_id, "Only the contract owner can grant the Admin role");
+        self.internal_set_role(&user_id, role, None);
+      },
+      Roles::Manager => {
+        self.internal_assert_admin(&caller);
+        self.internal_assert_can_manage(&caller, &user_id);
+        self.internal_set_role(&user_id, role, None);
+      },
+      Roles::Instructor => {
+        if self.internal_is_admin(&caller) {
+          self.internal_assert_can_manage(&caller, &user_id);
+          self.internal_set_role(&user_id, role, None);
+        } else {
+          self.internal_assert_reviewer(&caller);
+          assert!(user.metadata.role == Roles::Subscriber, "A manager can only promote a Subscriber");
+          self.internal_set_role(&user_id, role, Some(caller.clone()));
+        }
+      },
+    }
+
+    emit_role_update(&user_id, role, caller);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    self.user_metadata_by_id.get(&user_id).unwrap()
+  }
+
+  #[payable]
+  fn revoke_role(&mut self, user_id: UserId) -> JsonUser {
+    let initial_storage = env::storage_usage();
+    let caller = env::predecessor_account_id();
+    self.internal_assert_can_manage(&caller, &user_id);
+    assert!(
+      self.user_metadata_by_id.get(&user_id).unwrap().metadata.role != Roles::Subscriber,
+      "This user is already a Subscriber"
+    );
+
+    self.internal_set_role(&user_id, Roles::Subscriber, None);
+
+    emit_role_update(&user_id, Roles::Subscriber, caller);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    self.user_metadata_by_id.get(&user_id).unwrap()
+  }
+
+  #[payable]
+  fn suspend_user(&mut self, user_id: UserId, reason: String) {
+    let initial_storage = env::storage_usage();
+    let caller = env::predecessor_account_id();
+    self.internal_assert_can_manage(&caller, &user_id);
+
+    let mut user = self.user_metadata_by_id.get(&user_id).unwrap();
+    assert!(!user.metadata.suspended, "This user is already suspended");
+    user.metadata.suspended = true;
+    self.user_metadata_by_id.insert(&user_id, &user);
+
+    EventLog::elearning(EventLogVariant::UserSuspend(vec![UserSuspendLog {
+      user_id,
+      suspended: true,
+      updated_by: caller,
+      reason: Some(reason),
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  #[payable]
+  fn unsuspend_user(&mut self, user_id: UserId) {
+    let initial_storage = env::storage_usage();
+    let caller = env::predecessor_account_id();
+    self.internal_assert_can_manage(&caller, &user_id);
+
+    let mut user = self.user_metadata_by_id.get(&user_id).unwrap();
+    assert!(user.metadata.suspended, "This user is not suspended");
+    user.metadata.suspended = false;
+    self.user_metadata_by_id.insert(&user_id, &user);
+
+    EventLog::elearning(EventLogVariant::UserSuspend(vec![UserSuspendLog {
+      user_id,
+      suspended: false,
+      updated_by: caller,
+      reason: None,
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  #[payable]
+  fn delist_course(&mut self, course_id: CourseId, reason: String) {
+    let initial_storage = env::storage_usage();
+    let caller = env::predecessor_account_id();
+    let mut course = self.course_metadata_by_id.get(&course_id).expect("This course is not exist");
+    self.internal_assert_can_manage(&caller, &course.instructor_id);
+    assert!(!course.delisted, "This course is already delisted");
+
+    course.delisted = true;
+    self.course_metadata_by_id.insert(&course_id, &course);
+
+    EventLog::elearning(EventLogVariant::CourseDelist(vec![CourseDelistLog {
+      course_id,
+      delisted: true,
+      updated_by: caller,
+      reason: Some(reason),
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  #[payable]
+  fn relist_course(&mut self, course_id: CourseId) {
+    let initial_storage = env::storage_usage();
+    let caller = env::predecessor_account_id();
+    let mut course = self.course_metadata_by_id.get(&course_id).expect("This course is not exist");
+    self.internal_assert_can_manage(&caller, &course.instructor_id);
+    assert!(course.delisted, "This course is not delisted");
+
+    course.delisted = false;
+    self.course_metadata_by_id.insert(&course_id, &course);
+
+    EventLog::elearning(EventLogVariant::CourseDelist(vec![CourseDelistLog {
+      course_id,
+      delisted: false,
+      updated_by: caller,
+      reason: None,
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  #[payable]
+  fn revoke_certificate(&mut self, certificate_id: CertificateId, reason: String) {
+    let initial_storage = env::storage_usage();
+    let caller = env::predecessor_account_id();
+    let certificate = self.certificate_metadata_by_id.get(&certificate_id).expect("This Certificate is not exist");
+    let course = self.course_metadata_by_id.get(&certificate.course_id).expect("This course is not exist");
+    self.internal_assert_can_manage(&caller, &course.instructor_id);
+
+    self.internal_burn_certificate(&certificate_id);
+
+    // Certificate is a NEP-171 token
+    EventLog::nft(EventLogVariant::NftBurn(vec![NftBurnLog {
+      owner_id: certificate.student,
+      token_ids: vec![certificate_id],
+      authorized_id: Some(caller),
+      memo: Some(reason),
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  fn set_platform_fee(&mut self, platform_fee: u32) {
+    assert!(env::predecessor_account_id() == self.owner_id, "Only the contract owner can do this");
+    assert!(platform_fee <= MAX_PLATFORM_FEE, "The platform fee is more than {} basis points", MAX_PLATFORM_FEE);
+    self.platform_fee = platform_fee;
+  }
+
+  fn get_platform_fee(&self) -> u32 {
+    self.platform_fee
+  }
+
+  #[payable]
+  fn add_accepted_token(&mut self, token_id: AccountId) -> bool {
+    let initial_storage = env::storage_usage();
+    self.internal_assert_admin(&env::predecessor_account_id());
+    let added = self.accepted_tokens.insert(&token_id);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    added
+  }
+
+  #[payable]
+  fn remove_accepted_token(&mut self, token_id: AccountId) -> bool {
+    let initial_storage = env::storage_usage();
+    self.internal_assert_admin(&env::predecessor_account_id());
+    let removed = self.accepted_tokens.remove(&token_id);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    removed
+  }
+
+  fn get_accepted_tokens(&self) -> Vec<AccountId> {
+    self.accepted_tokens.to_vec()
+  }
+
+  fn is_admin(&self, account_id: UserId) -> bool {
+    self.internal_is_admin(&account_id)
+  }
+
+  fn get_instructors_per_manager(
+    &self,
+    manager_id: UserId,
+    from_index: Option<u32>,
+    limit: Option<u32>,
+  ) -> Vec<JsonUser> {
+    let instructor_set = match self.instructors_per_manager.get(&manager_id) {
+      Some(instructor_set) => instructor_set,
+      None => return vec![],
+    };
+
+    instructor_set
+      .iter()
+      .skip(from_index.unwrap_or(0) as usize)
+      .take(limit.unwrap_or(20) as usize)
+      .map(|user_id| self.user_metadata_by_id.get(&user_id).unwrap())
+      .collect()
+  }
+}
+
+fn emit_role_update(user_id: &UserId, role: Roles, updated_by: AccountId) {
+  EventLog::elearning(EventLogVariant::RoleUpdate(vec![RoleUpdateLog {
+    user_id: user_id.clone(),
+    role: format!("{:?}", role),
+    updated_by,
+  }]))
+  .emit();
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::test_utils::accounts;
+  use near_sdk::{json_types::U128, ONE_NEAR};
+
+  use super::*;
+  use crate::test_utils::{create_user, new_contract, set_caller};
+  use crate::models::{course::CourseFeatures, user::ImplUser};
+
+  /// accounts(0) owns the contract. accounts(1) and accounts(2) are managers, accounts(3) a subscriber
+  fn setup() -> ELearningContract {
+    let mut contract = new_contract();
+    for index in 1..4 {
+      create_user(&mut contract, accounts(index));
+    }
+
+    set_caller(accounts(0), ONE_NEAR);
+    contract.grant_role(accounts(1), Roles::Manager);
+    contract.grant_role(accounts(2), Roles::Manager);
+    contract
+  }
+
+  #[test]
+  fn manager_manages_its_instructors() {
+    let mut contract = setup();
+
+    set_caller(accounts(1), ONE_NEAR);
+    contract.grant_role(accounts(3), Roles::Instructor);
+    assert_eq!(contract.check_user_role(accounts(3)), Roles::Instructor);
+    assert_eq!(contract.get_instructors_per_manager(accounts(1), None, None).len(), 1);
+
+    set_caller(accounts(3), ONE_NEAR);
+    let course = contract.create_course("Rust".to_string(), None, None, U128(0), None);
+
+    set_caller(accounts(1), ONE_NEAR);
+    contract.delist_course(course.course_id.clone(), "Plagiarism".to_string());
+    contract.revoke_role(accounts(3));
+
+    assert!(contract.get_course_metadata_by_course_id(course.course_id).unwrap().delisted);
+    assert_eq!(contract.check_user_role(accounts(3)), Roles::Subscriber);
+    assert!(contract.get_instructors_per_manager(accounts(1), None, None).is_empty());
+  }
+
+  #[test]
+  #[should_panic(expected = "You are not an admin or the manager of this user")]
+  fn manager_cannot_revoke_other_instructors() {
+    let mut contract = setup();
+    set_caller(accounts(1), ONE_NEAR);
+    contract.grant_role(accounts(3), Roles::Instructor);
+
+    set_caller(accounts(2), ONE_NEAR);
+    contract.revoke_role(accounts(3));
+  }
+
+  #[test]
+  #[should_panic(expected = "Only the contract owner can grant the Admin role")]
+  fn only_owner_grants_admin() {
+    let mut contract = setup();
+    set_caller(accounts(0), ONE_NEAR);
+    contract.grant_role(accounts(1), Roles::Admin);
+
+    set_caller(accounts(1), ONE_NEAR);
+    contract.grant_role(accounts(3), Roles::Admin);
+  }
+
+  #[test]
+  #[should_panic(expected = "Your account is suspended")]
+  fn suspended_user_cannot_update_information() {
+    let mut contract = setup();
+    contract.suspend_user(accounts(3), "Spam".to_string());
+
+    set_caller(accounts(3), ONE_NEAR);
+    contract.update_user_information(Some("spammer".to_string()), None, None, None, None, None);
+  }
+}
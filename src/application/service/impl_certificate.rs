@@ -1,5 +1,6 @@
 #![allow(clippy::too_many_arguments)]
 use super::super::repository::convert_to_certificate_id;
+use crate::event::{EventLog, EventLogVariant, NftMintLog};
 use crate::models::{
   certificate::{CertificateFeatures, CertificateId, CertificateMetadata},
   contract::{ELearningContract, ELearningContractExt},
@@ -12,6 +13,7 @@ use near_sdk::{env, near_bindgen};
 #[near_bindgen]
 /// Implement function for certificate
 impl CertificateFeatures for ELearningContract {
+  #[payable]
   fn mint_certificate(
     &mut self,
     course_id: CourseId,
@@ -21,28 +23,30 @@ impl CertificateFeatures for ELearningContract {
     credit: u32,
     description: Option<String>,
   ) {
+    let initial_storage = env::storage_usage();
+
     // this function only for course owner
-    let check_owner = env::signer_account_id();
+    let check_owner = env::predecessor_account_id();
     let course = self.course_metadata_by_id.get(&course_id).unwrap();
     assert!(check_owner == course.instructor_id, "You are not the course owner");
+    self.internal_assert_not_suspended(&check_owner);
     assert!(course.students_studying_map.contains_key(&student_id), "This user is not a student in course");
     assert!(course.students_completed.contains_key(&student_id), "Student are not completed the course");
 
     let certificate_id = convert_to_certificate_id(&course_id, &student_id);
-    assert!(
-      !self.user_metadata_by_id.get(&student_id).unwrap().certificate.contains(&certificate_id),
-      "This certificate already exist"
-    );
+    assert!(!self.certificate_metadata_by_id.contains_key(&certificate_id), "This certificate already exist");
 
     // New certificate data
     let certificate_metadata = CertificateMetadata {
       certificate_id: certificate_id.clone(),
       student: student_id.clone(),
+      course_id,
       media,
       skill_id,
       credit,
       certificate_used: false,
       description,
+      issued_at: env::block_timestamp_ms(),
     };
 
     // Storage certificate in system contract
@@ -51,6 +55,17 @@ impl CertificateFeatures for ELearningContract {
     // Storage certificate in student's data
     //self.certificate_per_user.insert(&student, &certificate_id);
     self.internal_add_certificate_to_user(&student_id, &certificate_id);
+    self.all_certificates.insert(&certificate_id);
+
+    // Certificate is a NEP-171 token
+    EventLog::nft(EventLogVariant::NftMint(vec![NftMintLog {
+      owner_id: student_id,
+      token_ids: vec![certificate_id],
+      memo: None,
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
   }
 
   /// Get all certicicate by user id
@@ -73,12 +88,6 @@ impl CertificateFeatures for ELearningContract {
 
   /// Get certificate metadata by certificate id
   fn get_certificate_metadata_by_certificate_id(&self, certificate_id: CertificateId) -> Option<CertificateMetadata> {
-    /* uncomment this code when use event */
-    //assert!(self.certificate_metadata_by_id.contains_key(&certificate_id), "Certificate is not exsist");
-    if let Some(data) = self.certificate_metadata_by_id.get(&certificate_id) {
-      Some(data)
-    } else {
-      None
-    }
+    self.certificate_metadata_by_id.get(&certificate_id)
   }
 }
@@ -1,54 +1,79 @@
-use super::super::repository::convert_to_certificate_id;
+use super::super::repository::{assert_one_yocto, convert_to_certificate_id};
 use crate::{
   application::repository::convert_coure_title_to_cousrse_id,
+  content::ContentRef,
+  event::{CourseCompleteLog, CourseRefundLog, EscrowReleaseLog, EventLog, EventLogVariant, LessonCompleteLog},
+  fungible_token,
   models::{
     contract::{ELearningContract, ELearningContractExt},
-    course::{CourseFeatures, CourseId, CourseMetadata},
-    user::{ImplUser, Roles, UserId},
+    course::{CourseEscrow, CourseFeatures, CourseId, CourseMetadata, DEFAULT_REFUND_PERIOD, MAX_REFUND_PERIOD},
+    user::{Roles, UserId},
   },
 };
-use near_sdk::{env, near_bindgen, Balance};
+use near_sdk::{env, ext_contract, json_types::U128, near_bindgen, AccountId, Gas, PromiseResult};
 use std::collections::HashMap;
 
+const GAS_FOR_RESOLVE_REFUND: Gas = Gas(10_000_000_000_000);
+
+#[ext_contract(ext_self)]
+pub trait CourseSelf {
+  fn resolve_refund(&mut self, course_id: CourseId, escrow: CourseEscrow, enrolled_at: u64);
+  fn resolve_payout(&mut self, receiver_id: AccountId, token_id: Option<AccountId>, amount: U128);
+}
+
 #[near_bindgen]
 impl CourseFeatures for ELearningContract {
+  #[payable]
   fn create_course(
     &mut self,
     title: String,
     description: Option<String>,
     media: Option<String>,
-    price: Balance,
+    price: U128,
+    content: Option<ContentRef>,
   ) -> CourseMetadata {
-    let instructor_id = env::signer_account_id();
+    let initial_storage = env::storage_usage();
+    let instructor_id = env::predecessor_account_id();
     let course_id = convert_coure_title_to_cousrse_id(&title, instructor_id.to_string());
+    self.internal_assert_not_suspended(&instructor_id);
     assert!(
       self.user_metadata_by_id.get(&instructor_id).unwrap().metadata.role == Roles::Instructor,
-      "You aren't an instructor, You need register & upload your resume to become a instructor!"
+      "You aren't an instructor, You need to apply with your resume to become an instructor!"
     );
     assert!(
       !self.course_metadata_by_id.contains_key(&course_id),
       "Please! Change your title course, it already exists"
     );
+    if let Some(content) = &content {
+      content.assert_valid();
+    }
 
     let course_metadata = CourseMetadata {
       course_id: course_id.clone(),
       title,
       price,
+      price_token: None,
       media,
       description,
       instructor_id: instructor_id.clone(),
       created_at: env::block_timestamp_ms(),
       students_completed: HashMap::new(),
+      lessons_completed: HashMap::new(),
       students_studying_map: HashMap::new(),
       rating: 0,
       rating_count: 0,
-      content: "".to_string(),
+      content,
+      certificate_transferable: false,
+      delisted: false,
+      pool_id: None,
+      refund_period: DEFAULT_REFUND_PERIOD,
     };
     self.course_metadata_by_id.insert(&course_id, &course_metadata);
     let mut user = self.user_metadata_by_id.get(&instructor_id).unwrap();
     user.metadata.courses_owned += 1;
     self.user_metadata_by_id.insert(&instructor_id, &user);
     self.internal_add_course_to_instructor(&instructor_id, &course_id);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
     course_metadata
   }
 
@@ -79,25 +104,19 @@ impl CourseFeatures for ELearningContract {
 
   #[payable]
   fn payment_course(&mut self, course_id: CourseId) {
+    let initial_storage = env::storage_usage();
     // Check course has exists
-    let mut course = self.course_metadata_by_id.get(&course_id);
-    let user_id = env::signer_account_id();
-    assert!(course.is_some(), "The course doesn't exists");
-    assert!(self.user_metadata_by_id.contains_key(&user_id), "You need registration to use platform!");
-    assert!(user_id != course.clone().unwrap().instructor_id, "You own the course");
-    assert!(!course.clone().unwrap().students_studying_map.contains_key(&user_id), "You already have this course!");
-
-    // Plus 1 student to course owner
-    let mut coure_owner = self.user_metadata_by_id.get(&course.clone().unwrap().instructor_id).unwrap();
-    coure_owner.metadata.students += 1;
-    self.user_metadata_by_id.insert(&coure_owner.user_id, &coure_owner);
-
-    // Storage new course data
-    course.as_mut().unwrap().students_studying_map.insert(user_id.clone(), env::block_timestamp_ms());
-    let mut user = self.get_user_metadata_by_user_id(&user_id).unwrap();
-    user.courses.push(course_id.clone());
-    self.user_metadata_by_id.insert(&user_id, &user);
-    self.course_metadata_by_id.insert(&course_id, &course.unwrap());
+    let course = self.course_metadata_by_id.get(&course_id).expect("The course doesn't exists");
+    assert!(course.price_token.is_none(), "The price of this course is paid with ft_transfer_call");
+
+    // Check the payment
+    let deposit = env::attached_deposit();
+    assert!(deposit >= course.price.0, "Attach at least {} yoctoNEAR to buy this course", course.price.0);
+    let price = course.price.0;
+    self.internal_enroll(env::predecessor_account_id(), course);
+
+    // What is left of the deposit after the price pays for storage, then it is refunded
+    self.storage_balances.charge_caller(initial_storage, deposit - price);
   }
 
   /// Get all the course per user have. Current and complete course
@@ -132,13 +151,39 @@ impl CourseFeatures for ELearningContract {
     course_set.students_completed.contains_key(&user_id)
   }
 
+  #[payable]
+  fn complete_lesson(&mut self, course_id: CourseId, user_id: UserId) -> u32 {
+    let initial_storage = env::storage_usage();
+    let mut course = self.course_metadata_by_id.get(&course_id).expect("This course is not exist");
+    assert!(env::predecessor_account_id() == course.instructor_id, "You are not the course owner");
+    self.internal_assert_not_suspended(&course.instructor_id);
+    assert!(course.students_studying_map.contains_key(&user_id), "This user is not a student in this course");
+
+    let lessons_completed = course.lessons_completed.get(&user_id).copied().unwrap_or(0) + 1;
+    course.lessons_completed.insert(user_id.clone(), lessons_completed);
+    self.course_metadata_by_id.insert(&course_id, &course);
+
+    EventLog::elearning(EventLogVariant::LessonComplete(vec![LessonCompleteLog {
+      course_id,
+      student_id: user_id,
+      lessons_completed,
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    lessons_completed
+  }
+
   // TODO: More Requirement to check
+  #[payable]
   fn make_user_finish_course(&mut self, course_id: CourseId, user_id: UserId) {
-    let check_user = env::signer_account_id();
+    let initial_storage = env::storage_usage();
+    let check_user = env::predecessor_account_id();
     let mut course = self.course_metadata_by_id.get(&course_id).unwrap();
 
     // Check the courser owner
     assert!(check_user == course.instructor_id, "You are not the course owner");
+    self.internal_assert_not_suspended(&check_user);
     // Check user are student in this course or not
     assert!(course.students_studying_map.contains_key(&user_id), "This user is not a student in this course");
     // Check: has student complete the course yet
@@ -148,18 +193,359 @@ impl CourseFeatures for ELearningContract {
       "This student already completed the course"
     );
     // Update new data
-    course.students_completed.insert(user_id, env::block_timestamp_ms());
+    course.students_completed.insert(user_id.clone(), env::block_timestamp_ms());
+    self.course_metadata_by_id.insert(&course_id, &course);
+
+    EventLog::elearning(EventLogVariant::CourseComplete(vec![CourseCompleteLog { course_id, student_id: user_id }]))
+      .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  fn set_certificate_transferable(&mut self, course_id: CourseId, transferable: bool) {
+    let mut course = self.course_metadata_by_id.get(&course_id).expect("This course is not exist");
+    assert!(env::predecessor_account_id() == course.instructor_id, "You are not the course owner");
+    self.internal_assert_not_suspended(&course.instructor_id);
+
+    course.certificate_transferable = transferable;
+    self.course_metadata_by_id.insert(&course_id, &course);
+  }
+
+  #[payable]
+  fn set_course_content(&mut self, course_id: CourseId, content: ContentRef) {
+    let initial_storage = env::storage_usage();
+    let mut course = self.course_metadata_by_id.get(&course_id).expect("This course is not exist");
+    assert!(env::predecessor_account_id() == course.instructor_id, "You are not the course owner");
+    self.internal_assert_not_suspended(&course.instructor_id);
+    content.assert_valid();
+
+    course.content = Some(content);
     self.course_metadata_by_id.insert(&course_id, &course);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  #[payable]
+  fn set_price_token(&mut self, course_id: CourseId, price_token: Option<AccountId>) {
+    let initial_storage = env::storage_usage();
+    let mut course = self.course_metadata_by_id.get(&course_id).expect("This course is not exist");
+    assert!(env::predecessor_account_id() == course.instructor_id, "You are not the course owner");
+    self.internal_assert_not_suspended(&course.instructor_id);
+    self.accepted_tokens.assert_accepted(&price_token);
+    assert!(price_token.is_none() || course.pool_id.is_none(), "A course in a pool must be priced in NEAR");
+    // Escrowed payments are released in the price token, so they must all be in the same token
+    assert!(!self.escrows_per_course.contains_key(&course_id), "Release or refund the escrowed payments first");
+
+    course.price_token = price_token;
+    self.course_metadata_by_id.insert(&course_id, &course);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  fn set_refund_period(&mut self, course_id: CourseId, refund_period: u64) {
+    let mut course = self.course_metadata_by_id.get(&course_id).expect("This course is not exist");
+    assert!(env::predecessor_account_id() == course.instructor_id, "You are not the course owner");
+    self.internal_assert_not_suspended(&course.instructor_id);
+    assert!(refund_period <= MAX_REFUND_PERIOD, "The refund period can not be more than {} ms", MAX_REFUND_PERIOD);
+
+    course.refund_period = refund_period;
+    self.course_metadata_by_id.insert(&course_id, &course);
+  }
+
+  fn request_refund(&mut self, course_id: CourseId) {
+    let student_id = env::predecessor_account_id();
+    let mut course = self.course_metadata_by_id.get(&course_id).expect("This course is not exist");
+    assert!(!course.students_completed.contains_key(&student_id), "You already completed this course");
+    assert!(!course.lessons_completed.contains_key(&student_id), "You already completed a lesson of this course");
+    let escrow = self.internal_remove_escrow(&course_id, &student_id).expect("You have no refundable payment");
+    assert!(env::block_timestamp_ms() < escrow.release_at, "The refund period has ended");
+
+    // Leave the course
+    let enrolled_at = course.students_studying_map.remove(&student_id).unwrap();
+    self.course_metadata_by_id.insert(&course_id, &course);
+    let mut user = self.user_metadata_by_id.get(&student_id).unwrap();
+    user.courses.retain(|id| id != &course_id);
+    self.user_metadata_by_id.insert(&student_id, &user);
+    let mut instructor = self.user_metadata_by_id.get(&course.instructor_id).unwrap();
+    instructor.metadata.students -= 1;
+    self.user_metadata_by_id.insert(&course.instructor_id, &instructor);
+
+    // Leave before the transfer. The callback restores the escrow and the enrollment if the transfer fails
+    let resolve = ext_self::ext(env::current_account_id()).with_static_gas(GAS_FOR_RESOLVE_REFUND).resolve_refund(
+      course_id,
+      escrow.clone(),
+      enrolled_at,
+    );
+    fungible_token::transfer(&escrow.token_id, &student_id, escrow.amount.0).then(resolve);
+  }
+
+  #[private]
+  fn resolve_refund(&mut self, course_id: CourseId, escrow: CourseEscrow, enrolled_at: u64) {
+    if let PromiseResult::Successful(_) = env::promise_result(0) {
+      EventLog::elearning(EventLogVariant::CourseRefund(vec![CourseRefundLog {
+        course_id,
+        student_id: escrow.student_id,
+        amount: escrow.amount,
+        token_id: escrow.token_id,
+      }]))
+      .emit();
+      return;
+    }
+
+    let student_id = escrow.student_id.clone();
+    let mut course = self.course_metadata_by_id.get(&course_id).unwrap();
+    course.students_studying_map.insert(student_id.clone(), enrolled_at);
+    self.course_metadata_by_id.insert(&course_id, &course);
+    let mut user = self.user_metadata_by_id.get(&student_id).unwrap();
+    user.courses.push(course_id.clone());
+    self.user_metadata_by_id.insert(&student_id, &user);
+    let mut instructor = self.user_metadata_by_id.get(&course.instructor_id).unwrap();
+    instructor.metadata.students += 1;
+    self.user_metadata_by_id.insert(&course.instructor_id, &instructor);
+    self.internal_add_escrow(&course_id, &escrow);
+  }
+
+  fn release_escrow(&mut self, course_id: CourseId, limit: Option<u32>) -> U128 {
+    let course = self.course_metadata_by_id.get(&course_id).expect("This course is not exist");
+    assert!(env::predecessor_account_id() == course.instructor_id, "You are not the course owner");
+    self.internal_assert_not_suspended(&course.instructor_id);
+
+    let now = env::block_timestamp_ms();
+    let releasable: Vec<CourseEscrow> = match self.escrows_per_course.get(&course_id) {
+      Some(escrow_map) => escrow_map
+        .values()
+        .filter(|escrow| now >= escrow.release_at || course.students_completed.contains_key(&escrow.student_id))
+        .take(limit.unwrap_or(20) as usize)
+        .collect(),
+      None => vec![],
+    };
+    assert!(!releasable.is_empty(), "There is no payment to release");
+
+    let mut released = 0;
+    let mut platform_total = 0;
+    let mut instructor_total = 0;
+    for escrow in releasable.iter() {
+      self.internal_remove_escrow(&course_id, &escrow.student_id);
+      let (platform_amount, instructor_amount) =
+        self.internal_split_course_payment(&escrow.pool_id, escrow.amount.0, escrow.platform_fee);
+      released += escrow.amount.0;
+      platform_total += platform_amount;
+      instructor_total += instructor_amount;
+    }
+    self.internal_pay_out(&course.instructor_id, &course.price_token, platform_total, instructor_total);
+
+    EventLog::elearning(EventLogVariant::EscrowRelease(vec![EscrowReleaseLog {
+      course_id,
+      student_ids: releasable.into_iter().map(|escrow| escrow.student_id).collect(),
+      amount: U128(released),
+      token_id: course.price_token,
+    }]))
+    .emit();
+
+    U128(released)
+  }
+
+  #[private]
+  fn resolve_payout(&mut self, receiver_id: AccountId, token_id: Option<AccountId>, amount: U128) {
+    if let PromiseResult::Successful(_) = env::promise_result(0) {
+      return;
+    }
+    self.internal_credit_payout(&receiver_id, &token_id, amount.0);
+  }
+
+  #[payable]
+  fn withdraw_payout(&mut self, token_id: Option<AccountId>) -> U128 {
+    assert_one_yocto();
+    let account_id = env::predecessor_account_id();
+
+    // Debit before the transfer. The callback credits it back if the transfer fails
+    let amount = self.payout_balances.remove(&(account_id.clone(), token_id.clone())).unwrap_or(0);
+    assert!(amount > 0, "Nothing to withdraw");
+    self.internal_send_payout(&account_id, &token_id, amount);
+    U128(amount)
+  }
+
+  fn get_payout_balance(&self, account_id: AccountId, token_id: Option<AccountId>) -> U128 {
+    U128(self.payout_balances.get(&(account_id, token_id)).unwrap_or(0))
+  }
+
+  fn get_course_escrows(&self, course_id: CourseId, start: Option<u32>, limit: Option<u32>) -> Vec<CourseEscrow> {
+    match self.escrows_per_course.get(&course_id) {
+      Some(escrow_map) => {
+        escrow_map.values().skip(start.unwrap_or(0) as usize).take(limit.unwrap_or(20) as usize).collect()
+      },
+      None => vec![],
+    }
   }
 
   /// Get all the course per user have. Current and complete course
   fn get_course_metadata_by_course_id(&self, course_id: CourseId) -> Option<CourseMetadata> {
-    /* uncomment this code when use event*/
-    //assert!(self.course_metadata_by_id.contains_key(&course_id), "This course is not exist");
-    if let Some(course) = self.course_metadata_by_id.get(&course_id) {
-      Some(course)
-    } else {
-      None
+    self.course_metadata_by_id.get(&course_id)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::test_utils::{accounts, VMContextBuilder};
+  use near_sdk::{testing_env, Balance, RuntimeFeesConfig, VMConfig, ONE_NEAR};
+
+  use super::*;
+  use crate::models::admin::AdminFeatures;
+  use crate::storage::StorageManagement;
+  use crate::test_utils::{create_user, new_contract, set_caller, transferred_deposits};
+
+  /// accounts(0) owns the contract with a 10% fee, accounts(1) sells a course at `price`, accounts(2) buys it
+  fn setup(price: Balance) -> (ELearningContract, CourseId) {
+    let mut contract = new_contract();
+    contract.set_platform_fee(1_000);
+    for index in 1..3 {
+      create_user(&mut contract, accounts(index));
     }
+
+    set_caller(accounts(0), ONE_NEAR);
+    contract.grant_role(accounts(1), Roles::Instructor);
+    set_caller(accounts(1), ONE_NEAR);
+    let course = contract.create_course("Rust".to_string(), None, None, U128(price), None);
+    (contract, course.course_id)
+  }
+
+  #[test]
+  #[should_panic(expected = "Attach at least 5000000000000000000000000 yoctoNEAR to buy this course")]
+  fn under_payment_is_rejected() {
+    let (mut contract, course_id) = setup(5 * ONE_NEAR);
+    set_caller(accounts(2), 4 * ONE_NEAR);
+    contract.payment_course(course_id);
+  }
+
+  #[test]
+  fn over_payment_is_refunded() {
+    let (mut contract, course_id) = setup(5 * ONE_NEAR);
+    contract.set_refund_period(course_id.clone(), 0);
+    set_caller(accounts(2), 6 * ONE_NEAR);
+    contract.payment_course(course_id.clone());
+
+    let transfers = transferred_deposits();
+    assert!(transfers.contains(&(accounts(0), ONE_NEAR / 2)));
+    assert!(transfers.contains(&(accounts(1), 45 * ONE_NEAR / 10)));
+    let refund = transfers.iter().find(|(receiver_id, _)| receiver_id == &accounts(2)).unwrap().1;
+    assert!(refund > 0 && refund < ONE_NEAR);
+    assert!(!contract.check_course_completed(course_id, accounts(2)));
+  }
+
+  #[test]
+  fn free_course_pays_nobody() {
+    let (mut contract, course_id) = setup(0);
+    set_caller(accounts(2), ONE_NEAR);
+    contract.storage_deposit(None, None);
+
+    set_caller(accounts(2), 0);
+    contract.payment_course(course_id.clone());
+
+    assert!(transferred_deposits().is_empty());
+    assert_eq!(contract.get_all_courses_per_user_own(accounts(2), None, None).len(), 1);
+  }
+
+  #[test]
+  fn refund_within_the_refund_period() {
+    let (mut contract, course_id) = setup(5 * ONE_NEAR);
+    set_caller(accounts(2), 5 * ONE_NEAR);
+    contract.storage_deposit(None, None);
+    contract.payment_course(course_id.clone());
+    assert_eq!(contract.get_course_escrows(course_id.clone(), None, None).len(), 1);
+
+    set_caller(accounts(2), 0);
+    contract.request_refund(course_id.clone());
+
+    assert_eq!(transferred_deposits(), vec![(accounts(2), 5 * ONE_NEAR)]);
+    assert!(contract.get_course_escrows(course_id.clone(), None, None).is_empty());
+    assert!(contract.get_all_courses_per_user_own(accounts(2), None, None).is_empty());
+  }
+
+  #[test]
+  #[should_panic(expected = "You already completed a lesson of this course")]
+  fn refund_after_a_lesson_is_rejected() {
+    let (mut contract, course_id) = setup(5 * ONE_NEAR);
+    set_caller(accounts(2), 5 * ONE_NEAR);
+    contract.storage_deposit(None, None);
+    contract.payment_course(course_id.clone());
+
+    set_caller(accounts(1), ONE_NEAR);
+    assert_eq!(contract.complete_lesson(course_id.clone(), accounts(2)), 1);
+
+    set_caller(accounts(2), 0);
+    contract.request_refund(course_id);
+  }
+
+  #[test]
+  fn failed_refund_restores_the_escrow() {
+    let (mut contract, course_id) = setup(5 * ONE_NEAR);
+    set_caller(accounts(2), 5 * ONE_NEAR);
+    contract.storage_deposit(None, None);
+    contract.payment_course(course_id.clone());
+    let escrow = contract.get_course_escrows(course_id.clone(), None, None).pop().unwrap();
+    let enrolled_at =
+      contract.get_course_metadata_by_course_id(course_id.clone()).unwrap().students_studying_map[&accounts(2)];
+
+    set_caller(accounts(2), 0);
+    contract.request_refund(course_id.clone());
+    assert!(contract.get_all_courses_per_user_own(accounts(2), None, None).is_empty());
+
+    // The transfer failed
+    let mut context = VMContextBuilder::new();
+    context.current_account_id(accounts(0)).predecessor_account_id(accounts(0));
+    testing_env!(
+      context.build(),
+      VMConfig::test(),
+      RuntimeFeesConfig::test(),
+      Default::default(),
+      vec![PromiseResult::Failed]
+    );
+    contract.resolve_refund(course_id.clone(), escrow, enrolled_at);
+
+    assert_eq!(contract.get_course_escrows(course_id.clone(), None, None).len(), 1);
+    assert_eq!(contract.get_all_courses_per_user_own(accounts(2), None, None).len(), 1);
+    assert_eq!(contract.user_metadata_by_id.get(&accounts(1)).unwrap().metadata.students, 1);
+  }
+
+  #[test]
+  fn instructor_releases_after_the_refund_period() {
+    let (mut contract, course_id) = setup(5 * ONE_NEAR);
+    set_caller(accounts(2), 5 * ONE_NEAR);
+    contract.storage_deposit(None, None);
+    contract.payment_course(course_id.clone());
+
+    let mut context = VMContextBuilder::new();
+    context
+      .current_account_id(accounts(0))
+      .signer_account_id(accounts(1))
+      .predecessor_account_id(accounts(1))
+      .block_timestamp((DEFAULT_REFUND_PERIOD + 1) * 1_000_000);
+    testing_env!(context.build());
+    assert_eq!(contract.release_escrow(course_id.clone(), None), U128(5 * ONE_NEAR));
+
+    let transfers = transferred_deposits();
+    assert!(transfers.contains(&(accounts(0), ONE_NEAR / 2)));
+    assert!(transfers.contains(&(accounts(1), 45 * ONE_NEAR / 10)));
+  }
+
+  #[test]
+  fn failed_payout_is_credited_and_withdrawn() {
+    let (mut contract, _) = setup(5 * ONE_NEAR);
+
+    // The transfer of the instructor part failed
+    let mut context = VMContextBuilder::new();
+    context.current_account_id(accounts(0)).predecessor_account_id(accounts(0));
+    testing_env!(
+      context.build(),
+      VMConfig::test(),
+      RuntimeFeesConfig::test(),
+      Default::default(),
+      vec![PromiseResult::Failed]
+    );
+    contract.resolve_payout(accounts(1), None, U128(45 * ONE_NEAR / 10));
+    assert_eq!(contract.get_payout_balance(accounts(1), None), U128(45 * ONE_NEAR / 10));
+
+    set_caller(accounts(1), 1);
+    assert_eq!(contract.withdraw_payout(None), U128(45 * ONE_NEAR / 10));
+    assert_eq!(transferred_deposits(), vec![(accounts(1), 45 * ONE_NEAR / 10)]);
+    assert_eq!(contract.get_payout_balance(accounts(1), None), U128(0));
   }
 }
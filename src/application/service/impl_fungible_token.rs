@@ -0,0 +1,127 @@
+use crate::{
+  fungible_token::FungibleTokenReceiver,
+  models::{
+    contract::{ELearningContract, ELearningContractExt},
+    course::CourseId,
+  },
+};
+use near_sdk::{env, json_types::U128, near_bindgen, serde::Deserialize, serde_json, AccountId, PromiseOrValue};
+
+/// Purchases a `msg` of `ft_transfer_call` can ask for. `{"action": "enroll", "course_id": "<course id>"}`
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde", tag = "action", rename_all = "snake_case")]
+enum TokenPurchase {
+  Enroll { course_id: CourseId },
+}
+
+#[near_bindgen]
+/// Implement NEP-141 payments of courses priced in a token
+impl FungibleTokenReceiver for ELearningContract {
+  fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+    let initial_storage = env::storage_usage();
+    let token_id = self.accepted_tokens.assert_caller_accepted();
+    let purchase: TokenPurchase = serde_json::from_str(&msg).expect("Invalid msg");
+
+    match purchase {
+      TokenPurchase::Enroll { course_id } => {
+        let course = self.course_metadata_by_id.get(&course_id).expect("The course doesn't exists");
+        assert!(course.price_token.as_ref() == Some(&token_id), "The price of this course is not paid in {}", token_id);
+        assert!(amount.0 >= course.price.0, "Transfer at least {} to buy this course", course.price.0);
+        let price = course.price.0;
+        self.internal_enroll(sender_id.clone(), course);
+
+        // The tokens pay the price, so storage comes from the storage balance of the student
+        self.storage_balances.charge(&sender_id, initial_storage, 0);
+        PromiseOrValue::Value(U128(amount.0 - price))
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::mock::VmAction;
+  use near_sdk::test_utils::{accounts, get_created_receipts};
+  use near_sdk::ONE_NEAR;
+
+  use super::*;
+  use crate::test_utils::{create_user, new_contract, set_caller, set_signer_and_caller};
+  use crate::models::{admin::AdminFeatures, course::CourseFeatures, user::Roles};
+  use crate::storage::StorageManagement;
+
+  /// `ft_transfer` calls created by the last call: (token, receiver, amount)
+  fn ft_transfers() -> Vec<(AccountId, AccountId, U128)> {
+    get_created_receipts()
+      .into_iter()
+      .flat_map(|receipt| {
+        receipt.actions.into_iter().filter_map(move |action| match action {
+          VmAction::FunctionCall { function_name, args, .. } if function_name == "ft_transfer" => {
+            let args: serde_json::Value = serde_json::from_slice(&args).unwrap();
+            let receiver_id = args["receiver_id"].as_str().unwrap().parse().unwrap();
+            let amount = U128(args["amount"].as_str().unwrap().parse().unwrap());
+            Some((receipt.receiver_id.clone(), receiver_id, amount))
+          },
+          _ => None,
+        })
+      })
+      .collect()
+  }
+
+  /// accounts(0) owns the contract with a 10% fee and accepts accounts(3) as token, accounts(1) sells a course
+  /// at 1000 tokens, accounts(2) buys it
+  fn setup() -> (ELearningContract, CourseId) {
+    let mut contract = new_contract();
+    contract.set_platform_fee(1_000);
+    contract.add_accepted_token(accounts(3));
+    for index in 1..3 {
+      create_user(&mut contract, accounts(index));
+      contract.storage_deposit(None, None);
+    }
+
+    set_caller(accounts(0), ONE_NEAR);
+    contract.grant_role(accounts(1), Roles::Instructor);
+    set_caller(accounts(1), ONE_NEAR);
+    let course = contract.create_course("Rust".to_string(), None, None, U128(1_000), None);
+    contract.set_price_token(course.course_id.clone(), Some(accounts(3)));
+    (contract, course.course_id)
+  }
+
+  fn enroll(contract: &mut ELearningContract, course_id: &CourseId, amount: u128) -> U128 {
+    set_signer_and_caller(accounts(2), accounts(3), 0);
+    let msg = format!(r#"{{"action":"enroll","course_id":"{}"}}"#, course_id);
+    match contract.ft_on_transfer(accounts(2), U128(amount), msg) {
+      PromiseOrValue::Value(unused) => unused,
+      PromiseOrValue::Promise(_) => panic!("Expected the unused amount"),
+    }
+  }
+
+  #[test]
+  fn enroll_and_refund_in_tokens() {
+    let (mut contract, course_id) = setup();
+    assert_eq!(enroll(&mut contract, &course_id, 1_200), U128(200));
+    let escrows = contract.get_course_escrows(course_id.clone(), None, None);
+    assert_eq!(escrows[0].token_id, Some(accounts(3)));
+
+    set_caller(accounts(2), 0);
+    contract.request_refund(course_id);
+    assert_eq!(ft_transfers(), vec![(accounts(3), accounts(2), U128(1_000))]);
+  }
+
+  #[test]
+  fn instructor_is_paid_in_tokens() {
+    let (mut contract, course_id) = setup();
+    set_caller(accounts(1), 0);
+    contract.set_refund_period(course_id.clone(), 0);
+
+    assert_eq!(enroll(&mut contract, &course_id, 1_000), U128(0));
+    assert_eq!(ft_transfers(), vec![(accounts(3), accounts(0), U128(100)), (accounts(3), accounts(1), U128(900))]);
+  }
+
+  #[test]
+  #[should_panic(expected = "The price of this course is paid with ft_transfer_call")]
+  fn token_priced_course_can_not_be_bought_with_near() {
+    let (mut contract, course_id) = setup();
+    set_caller(accounts(2), 1_000);
+    contract.payment_course(course_id);
+  }
+}
@@ -0,0 +1,178 @@
+use crate::application::repository::internal_instructor::assert_valid_application;
+use crate::content::ContentRef;
+use crate::event::{EventLog, EventLogVariant, InstructorApplicationLog};
+use crate::models::{
+  contract::{ELearningContract, ELearningContractExt},
+  instructor::{ApplicationStatus, InstructorApplication, InstructorApplicationFeatures},
+  user::{Roles, UserId},
+};
+use near_sdk::{env, near_bindgen};
+
+#[near_bindgen]
+/// Implement the instructor application workflow
+impl InstructorApplicationFeatures for ELearningContract {
+  #[payable]
+  fn apply_for_instructor(
+    &mut self,
+    resume: ContentRef,
+    portfolio: Option<String>,
+    categories: Vec<String>,
+  ) -> InstructorApplication {
+    let initial_storage = env::storage_usage();
+    let user_id = env::predecessor_account_id();
+    let user = self.user_metadata_by_id.get(&user_id).expect("You need registration to use platform!");
+    self.internal_assert_not_suspended(&user_id);
+    assert!(user.metadata.role == Roles::Subscriber, "Only a Subscriber can apply to become an instructor");
+    assert!(!self.pending_applications.contains(&user_id), "You already have a pending application");
+    resume.assert_valid();
+    assert_valid_application(&portfolio, &categories);
+
+    let application = InstructorApplication {
+      user_id: user_id.clone(),
+      resume,
+      portfolio,
+      categories,
+      status: ApplicationStatus::Pending,
+      reviewer_id: None,
+      reason: None,
+      submitted_at: env::block_timestamp_ms(),
+      reviewed_at: None,
+    };
+    self.instructor_applications.insert(&user_id, &application);
+    self.pending_applications.insert(&user_id);
+
+    EventLog::elearning(EventLogVariant::InstructorApplication(vec![InstructorApplicationLog {
+      user_id,
+      status: format!("{:?}", ApplicationStatus::Pending),
+      reviewer_id: None,
+      reason: None,
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    application
+  }
+
+  #[payable]
+  fn approve_instructor_application(&mut self, user_id: UserId, reason: Option<String>) -> InstructorApplication {
+    let initial_storage = env::storage_usage();
+    let application =
+      self.internal_review_application(env::predecessor_account_id(), &user_id, ApplicationStatus::Approved, reason);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    application
+  }
+
+  #[payable]
+  fn reject_instructor_application(&mut self, user_id: UserId, reason: String) -> InstructorApplication {
+    let initial_storage = env::storage_usage();
+    assert!(!reason.trim().is_empty(), "Give a reason for the rejection");
+    let application = self.internal_review_application(
+      env::predecessor_account_id(),
+      &user_id,
+      ApplicationStatus::Rejected,
+      Some(reason),
+    );
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    application
+  }
+
+  fn get_instructor_application(&self, user_id: UserId) -> Option<InstructorApplication> {
+    self.instructor_applications.get(&user_id)
+  }
+
+  fn get_pending_instructor_applications(
+    &self,
+    from_index: Option<u32>,
+    limit: Option<u32>,
+  ) -> Vec<InstructorApplication> {
+    self
+      .pending_applications
+      .iter()
+      .skip(from_index.unwrap_or(0) as usize)
+      .take(limit.unwrap_or(20) as usize)
+      .map(|user_id| self.instructor_applications.get(&user_id).unwrap())
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::test_utils::accounts;
+  use near_sdk::ONE_NEAR;
+
+  use super::*;
+  use crate::test_utils::{create_user, new_contract, set_caller};
+  use crate::models::{admin::AdminFeatures, user::ImplUser};
+
+  fn resume() -> ContentRef {
+    ContentRef {
+      uri: "ipfs://bafyresume".to_string(),
+      hash: [1u8; 32].into(),
+      size: 2048,
+      mime: "application/pdf".to_string(),
+    }
+  }
+
+  /// accounts(0) owns the contract, accounts(1) is a manager and accounts(2), accounts(3) are subscribers
+  fn setup() -> ELearningContract {
+    let mut contract = new_contract();
+    for index in 1..4 {
+      create_user(&mut contract, accounts(index));
+    }
+
+    set_caller(accounts(0), ONE_NEAR);
+    contract.grant_role(accounts(1), Roles::Manager);
+    contract
+  }
+
+  #[test]
+  fn manager_approves_application() {
+    let mut contract = setup();
+    set_caller(accounts(2), ONE_NEAR);
+    contract.apply_for_instructor(resume(), None, vec!["rust".to_string()]);
+    set_caller(accounts(3), ONE_NEAR);
+    contract.apply_for_instructor(resume(), None, vec!["design".to_string()]);
+    assert_eq!(contract.get_pending_instructor_applications(None, None).len(), 2);
+
+    set_caller(accounts(1), ONE_NEAR);
+    contract.approve_instructor_application(accounts(2), None);
+    contract.reject_instructor_application(accounts(3), "The resume is empty".to_string());
+
+    assert_eq!(contract.check_user_role(accounts(2)), Roles::Instructor);
+    assert_eq!(contract.get_instructors_per_manager(accounts(1), None, None).len(), 1);
+    assert_eq!(contract.check_user_role(accounts(3)), Roles::Subscriber);
+    let rejected = contract.get_instructor_application(accounts(3)).unwrap();
+    assert_eq!(rejected.status, ApplicationStatus::Rejected);
+    assert_eq!(rejected.reviewer_id, Some(accounts(1)));
+    assert!(contract.get_pending_instructor_applications(None, None).is_empty());
+
+    // A rejected user can apply again
+    set_caller(accounts(3), ONE_NEAR);
+    contract.apply_for_instructor(resume(), None, vec!["design".to_string()]);
+    assert_eq!(contract.get_pending_instructor_applications(None, None).len(), 1);
+  }
+
+  #[test]
+  #[should_panic(expected = "This user is suspended")]
+  fn suspended_applicant_is_not_approved() {
+    let mut contract = setup();
+    set_caller(accounts(2), ONE_NEAR);
+    contract.apply_for_instructor(resume(), None, vec!["rust".to_string()]);
+    set_caller(accounts(0), ONE_NEAR);
+    contract.suspend_user(accounts(2), "Spam".to_string());
+
+    set_caller(accounts(1), ONE_NEAR);
+    contract.approve_instructor_application(accounts(2), None);
+  }
+
+  #[test]
+  #[should_panic(expected = "Only an admin or a manager can do this")]
+  fn subscriber_cannot_approve() {
+    let mut contract = setup();
+    set_caller(accounts(2), ONE_NEAR);
+    contract.apply_for_instructor(resume(), None, vec!["rust".to_string()]);
+
+    set_caller(accounts(3), ONE_NEAR);
+    contract.approve_instructor_application(accounts(2), None);
+  }
+}
@@ -0,0 +1,156 @@
+use super::super::repository::assert_one_yocto;
+use crate::models::{
+  contract::{ELearningContract, ELearningContractExt, ELearningContractMetadata},
+  nft::{
+    JsonToken, NonFungibleTokenCore, NonFungibleTokenEnumeration, NonFungibleTokenMetadata, NonFungibleTokenResolver,
+    TokenId,
+  },
+};
+use near_sdk::{
+  env, ext_contract, json_types::U128, near_bindgen, serde_json, AccountId, Gas, PromiseOrValue, PromiseResult,
+};
+
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_NFT_ON_TRANSFER: Gas = Gas(25_000_000_000_000);
+
+#[ext_contract(ext_nft_receiver)]
+pub trait NonFungibleTokenReceiver {
+  /// Method stored on the receiver contract that is called via cross contract call when nft_transfer_call is called
+  /// Returns `true` if the token should be returned back to the sender.
+  fn nft_on_transfer(
+    &mut self,
+    sender_id: AccountId,
+    previous_owner_id: AccountId,
+    token_id: TokenId,
+    msg: String,
+  ) -> PromiseOrValue<bool>;
+}
+
+#[ext_contract(ext_self)]
+pub trait NonFungibleTokenSelf {
+  fn nft_resolve_transfer(
+    &mut self,
+    owner_id: AccountId,
+    receiver_id: AccountId,
+    token_id: TokenId,
+    memo: Option<String>,
+  ) -> bool;
+}
+
+#[near_bindgen]
+/// Implement NEP-171 for certificate
+impl NonFungibleTokenCore for ELearningContract {
+  #[payable]
+  fn nft_transfer(&mut self, receiver_id: AccountId, token_id: TokenId, approval_id: Option<u64>, memo: Option<String>) {
+    assert_one_yocto();
+    assert!(approval_id.is_none(), "Approvals are not supported");
+
+    let sender_id = env::predecessor_account_id();
+    self.internal_transfer_certificate(&sender_id, &receiver_id, &token_id, memo);
+  }
+
+  #[payable]
+  fn nft_transfer_call(
+    &mut self,
+    receiver_id: AccountId,
+    token_id: TokenId,
+    approval_id: Option<u64>,
+    memo: Option<String>,
+    msg: String,
+  ) -> PromiseOrValue<bool> {
+    assert_one_yocto();
+    assert!(approval_id.is_none(), "Approvals are not supported");
+
+    let sender_id = env::predecessor_account_id();
+    self.internal_transfer_certificate(&sender_id, &receiver_id, &token_id, memo.clone());
+
+    // Let the receiver decide whether the certificate should be given back
+    ext_nft_receiver::ext(receiver_id.clone())
+      .with_static_gas(GAS_FOR_NFT_ON_TRANSFER)
+      .nft_on_transfer(sender_id.clone(), sender_id.clone(), token_id.clone(), msg)
+      .then(
+        ext_self::ext(env::current_account_id())
+          .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+          .nft_resolve_transfer(sender_id, receiver_id, token_id, memo),
+      )
+      .into()
+  }
+
+  fn nft_token(&self, token_id: TokenId) -> Option<JsonToken> {
+    self.internal_json_token(&token_id)
+  }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for ELearningContract {
+  #[private]
+  fn nft_resolve_transfer(
+    &mut self,
+    owner_id: AccountId,
+    receiver_id: AccountId,
+    token_id: TokenId,
+    memo: Option<String>,
+  ) -> bool {
+    // Receiver keeps the certificate unless `nft_on_transfer` returned true
+    if let PromiseResult::Successful(value) = env::promise_result(0) {
+      if let Ok(return_token) = serde_json::from_slice::<bool>(&value) {
+        if !return_token {
+          return true;
+        }
+      }
+    }
+
+    // The receiver may already have transferred it
+    match self.certificate_metadata_by_id.get(&token_id) {
+      Some(certificate) if certificate.student == receiver_id => {},
+      _ => return true,
+    }
+
+    self.internal_move_certificate(&receiver_id, &owner_id, &token_id, memo);
+    false
+  }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenEnumeration for ELearningContract {
+  fn nft_total_supply(&self) -> U128 {
+    U128(self.all_certificates.len() as u128)
+  }
+
+  fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken> {
+    self
+      .all_certificates
+      .iter()
+      .skip(from_index.map(|i| i.0).unwrap_or(0) as usize)
+      .take(limit.unwrap_or(20) as usize)
+      .filter_map(|certificate_id| self.internal_json_token(&certificate_id))
+      .collect()
+  }
+
+  fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
+    self.certificate_per_user.get(&account_id).map(|set| U128(set.len() as u128)).unwrap_or(U128(0))
+  }
+
+  fn nft_tokens_for_owner(
+    &self,
+    account_id: AccountId,
+    from_index: Option<U128>,
+    limit: Option<u64>,
+  ) -> Vec<JsonToken> {
+    let certificate_set = if let Some(set) = self.certificate_per_user.get(&account_id) { set } else { return vec![] };
+
+    certificate_set
+      .iter()
+      .skip(from_index.map(|i| i.0).unwrap_or(0) as usize)
+      .take(limit.unwrap_or(20) as usize)
+      .filter_map(|certificate_id| self.internal_json_token(&certificate_id))
+      .collect()
+  }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenMetadata for ELearningContract {
+  fn nft_metadata(&self) -> ELearningContractMetadata {
+    self.metadata_contract.get().unwrap()
+  }
+}
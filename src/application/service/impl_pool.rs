@@ -0,0 +1,213 @@
+use super::super::repository::{assert_one_yocto, convert_to_pool_id};
+use crate::event::{EventLog, EventLogVariant, PoolCreateLog, PoolWithdrawLog};
+use crate::models::{
+  contract::{ELearningContract, ELearningContractExt},
+  course::CourseId,
+  pool::{PoolFeatures, PoolId, PoolMember, PoolMetadata, FULL_SHARE, MAX_POOL_MEMBERS},
+  user::{Roles, UserId},
+};
+use near_sdk::{env, ext_contract, json_types::U128, near_bindgen, Gas, Promise, PromiseResult};
+
+const GAS_FOR_RESOLVE_WITHDRAW: Gas = Gas(5_000_000_000_000);
+
+#[ext_contract(ext_self)]
+pub trait PoolSelf {
+  fn resolve_pool_withdraw(&mut self, pool_id: PoolId, account_id: UserId, amount: U128);
+}
+
+#[near_bindgen]
+/// Implement function for revenue pools
+impl PoolFeatures for ELearningContract {
+  #[payable]
+  fn create_pool(&mut self, name: String, members: Vec<(UserId, u32)>, sale_share: u32) -> PoolMetadata {
+    let initial_storage = env::storage_usage();
+    let creator_id = env::predecessor_account_id();
+    let creator = self.user_metadata_by_id.get(&creator_id).expect("You need registration to use platform!");
+    assert!(creator.metadata.role == Roles::Instructor, "Only an instructor can create a pool");
+    self.internal_assert_not_suspended(&creator_id);
+
+    let pool_id = convert_to_pool_id(&name, &creator_id);
+    assert!(!self.pool_metadata_by_id.contains_key(&pool_id), "Please! Change your pool name, it already exists");
+    assert!(
+      sale_share > 0 && sale_share <= FULL_SHARE,
+      "Sale share must be between 1 and {} basis points",
+      FULL_SHARE
+    );
+    assert!(!members.is_empty() && members.len() <= MAX_POOL_MEMBERS, "A pool has 1 to {} members", MAX_POOL_MEMBERS);
+
+    let total_share: u32 = members.iter().map(|(_, share)| *share).sum();
+    assert_eq!(FULL_SHARE, total_share, "Shares must sum to {} basis points", FULL_SHARE);
+    for (index, (member_id, share)) in members.iter().enumerate() {
+      assert!(*share > 0, "Every member must have a share");
+      assert!(!members[..index].iter().any(|(other_id, _)| other_id == member_id), "Members must be unique");
+      let is_instructor = match self.user_metadata_by_id.get(member_id) {
+        Some(user) => user.metadata.role == Roles::Instructor,
+        None => false,
+      };
+      assert!(is_instructor, "Every member of a pool must be an instructor");
+    }
+
+    let pool = PoolMetadata {
+      pool_id: pool_id.clone(),
+      name,
+      creator_id: creator_id.clone(),
+      members: members
+        .into_iter()
+        .map(|(account_id, share)| PoolMember { account_id, share, balance: U128(0) })
+        .collect(),
+      sale_share,
+      total_received: U128(0),
+      created_at: env::block_timestamp_ms(),
+    };
+    self.pool_metadata_by_id.insert(&pool_id, &pool);
+    for member in pool.members.iter() {
+      self.internal_add_pool_to_user(&member.account_id, &pool_id);
+    }
+
+    EventLog::elearning(EventLogVariant::PoolCreate(vec![PoolCreateLog {
+      pool_id,
+      creator_id,
+      members: pool.members.iter().map(|member| member.account_id.clone()).collect(),
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    pool
+  }
+
+  #[payable]
+  fn set_course_pool(&mut self, course_id: CourseId, pool_id: Option<PoolId>) {
+    let initial_storage = env::storage_usage();
+    let mut course = self.course_metadata_by_id.get(&course_id).expect("This course is not exist");
+    assert!(env::predecessor_account_id() == course.instructor_id, "You are not the course owner");
+    self.internal_assert_not_suspended(&course.instructor_id);
+
+    if let Some(pool_id) = &pool_id {
+      assert!(course.price_token.is_none(), "Only a course priced in NEAR can join a pool");
+      let pool = self.pool_metadata_by_id.get(pool_id).expect("This pool is not exist");
+      assert!(
+        pool.members.iter().any(|member| member.account_id == course.instructor_id),
+        "You are not a member of this pool"
+      );
+    }
+
+    course.pool_id = pool_id;
+    self.course_metadata_by_id.insert(&course_id, &course);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  #[payable]
+  fn withdraw_from_pool(&mut self, pool_id: PoolId, amount: Option<U128>) -> U128 {
+    assert_one_yocto();
+    let account_id = env::predecessor_account_id();
+    let mut pool = self.pool_metadata_by_id.get(&pool_id).expect("This pool is not exist");
+    let member = pool
+      .members
+      .iter_mut()
+      .find(|member| member.account_id == account_id)
+      .expect("You are not a member of this pool");
+
+    let amount = amount.map(|amount| amount.0).unwrap_or(member.balance.0);
+    assert!(amount > 0, "Nothing to withdraw");
+    assert!(amount <= member.balance.0, "The amount is greater than your balance");
+
+    // Debit before the transfer. The callback credits it back if the transfer fails
+    member.balance = U128(member.balance.0 - amount);
+    self.pool_metadata_by_id.insert(&pool_id, &pool);
+
+    let resolve = ext_self::ext(env::current_account_id())
+      .with_static_gas(GAS_FOR_RESOLVE_WITHDRAW)
+      .resolve_pool_withdraw(pool_id, account_id.clone(), U128(amount));
+    Promise::new(account_id).transfer(amount).then(resolve);
+
+    U128(amount)
+  }
+
+  #[private]
+  fn resolve_pool_withdraw(&mut self, pool_id: PoolId, account_id: UserId, amount: U128) {
+    if let PromiseResult::Successful(_) = env::promise_result(0) {
+      EventLog::elearning(EventLogVariant::PoolWithdraw(vec![PoolWithdrawLog { pool_id, account_id, amount }])).emit();
+      return;
+    }
+
+    let mut pool = self.pool_metadata_by_id.get(&pool_id).unwrap();
+    if let Some(member) = pool.members.iter_mut().find(|member| member.account_id == account_id) {
+      member.balance = U128(member.balance.0 + amount.0);
+      self.pool_metadata_by_id.insert(&pool_id, &pool);
+    }
+  }
+
+  fn get_pool_metadata(&self, pool_id: PoolId) -> Option<PoolMetadata> {
+    self.pool_metadata_by_id.get(&pool_id)
+  }
+
+  fn get_all_pools_per_user(&self, user_id: UserId, start: Option<u32>, limit: Option<u32>) -> Vec<PoolMetadata> {
+    let pool_set = match self.pools_per_user.get(&user_id) {
+      Some(pool_set) => pool_set,
+      None => return vec![],
+    };
+
+    pool_set
+      .iter()
+      .skip(start.unwrap_or(0) as usize)
+      .take(limit.unwrap_or(20) as usize)
+      .map(|pool_id| self.pool_metadata_by_id.get(&pool_id).unwrap())
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::test_utils::accounts;
+  use near_sdk::ONE_NEAR;
+
+  use super::*;
+  use crate::test_utils::{create_user, new_contract, set_caller};
+  use crate::models::{admin::AdminFeatures, course::CourseFeatures};
+
+  /// accounts(1) and accounts(2) are instructors, accounts(3) a student
+  fn setup() -> ELearningContract {
+    let mut contract = new_contract();
+    for index in 1..4 {
+      create_user(&mut contract, accounts(index));
+      contract.storage_balances.deposit(None, None);
+    }
+
+    set_caller(accounts(0), ONE_NEAR);
+    contract.grant_role(accounts(1), Roles::Instructor);
+    contract.grant_role(accounts(2), Roles::Instructor);
+    contract
+  }
+
+  #[test]
+  fn members_withdraw_their_share_of_sales() {
+    let mut contract = setup();
+    set_caller(accounts(1), ONE_NEAR);
+    let pool = contract.create_pool("Rust team".to_string(), vec![(accounts(1), 7_050), (accounts(2), 2_950)], 5_000);
+    let course = contract.create_course("Rust".to_string(), None, None, U128(10 * ONE_NEAR), None);
+    contract.set_course_pool(course.course_id.clone(), Some(pool.pool_id.clone()));
+    contract.set_refund_period(course.course_id.clone(), 0);
+
+    set_caller(accounts(3), 10 * ONE_NEAR);
+    contract.payment_course(course.course_id);
+
+    let pool = contract.get_pool_metadata(pool.pool_id).unwrap();
+    assert_eq!(pool.total_received, U128(5 * ONE_NEAR));
+    assert_eq!(pool.members[0].balance, U128(3_525 * ONE_NEAR / 1_000));
+    assert_eq!(pool.members[1].balance, U128(1_475 * ONE_NEAR / 1_000));
+
+    set_caller(accounts(2), 1);
+    assert_eq!(contract.withdraw_from_pool(pool.pool_id.clone(), None), U128(1_475 * ONE_NEAR / 1_000));
+    let pool = contract.get_pool_metadata(pool.pool_id).unwrap();
+    assert_eq!(pool.members[1].balance, U128(0));
+    assert_eq!(contract.get_all_pools_per_user(accounts(2), None, None).len(), 1);
+  }
+
+  #[test]
+  #[should_panic(expected = "Every member of a pool must be an instructor")]
+  fn members_must_be_instructors() {
+    let mut contract = setup();
+    set_caller(accounts(1), ONE_NEAR);
+    contract.create_pool("Rust team".to_string(), vec![(accounts(1), 5_000), (accounts(3), 5_000)], 5_000);
+  }
+}
@@ -1,3 +1,4 @@
+use crate::event::{EventLog, EventLogVariant, SkillMintLog};
 use crate::models::{
   certificate::CertificateId,
   contract::{ELearningContract, ELearningContractExt},
@@ -18,13 +19,17 @@ impl SkillFeatures for ELearningContract {
   }
 
   /// Mint a skill from certificate credit
+  #[payable]
   fn mint_skill_by_certificate(&mut self, certificate_id: CertificateId) {
+    let initial_storage = env::storage_usage();
+
     // Check certificate
     assert!(self.certificate_metadata_by_id.contains_key(&certificate_id), "This Certificate is not exist");
 
     // Check certificatw owner
     let mut certificate = self.certificate_metadata_by_id.get(&certificate_id).unwrap();
-    assert!(certificate.student == env::signer_account_id(), "This certificate is not belong to you");
+    assert!(certificate.student == env::predecessor_account_id(), "This certificate is not belong to you");
+    self.internal_assert_not_suspended(&certificate.student);
     assert!(!certificate.certificate_used, "This Certificate has been used");
 
     // Create new skillmetadata by skill id in system contract
@@ -42,7 +47,17 @@ impl SkillFeatures for ELearningContract {
     certificate.certificate_used = true;
     self.certificate_metadata_by_id.insert(&certificate_id, &certificate);
     // Add new skill credit for user
-    self.add_skill(certificate.student, certificate.skill_id, certificate.credit);
+    self.add_skill(certificate.student.clone(), certificate.skill_id.clone(), certificate.credit);
+
+    EventLog::elearning(EventLogVariant::SkillMint(vec![SkillMintLog {
+      skill_id: certificate.skill_id,
+      owner_id: certificate.student,
+      credit: certificate.credit,
+      certificate_id,
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
   }
 
   /// Get all skills per user
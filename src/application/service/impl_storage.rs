@@ -0,0 +1,27 @@
+use crate::{
+  models::contract::{ELearningContract, ELearningContractExt},
+  storage::{StorageBalance, StorageBalanceBounds, StorageLedger, StorageManagement},
+};
+use near_sdk::{json_types::U128, near_bindgen, AccountId};
+
+#[near_bindgen]
+/// Implement NEP-145 storage management
+impl StorageManagement for ELearningContract {
+  #[payable]
+  fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+    self.storage_balances.deposit(account_id, registration_only)
+  }
+
+  #[payable]
+  fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+    self.storage_balances.withdraw(amount)
+  }
+
+  fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+    self.storage_balances.balance_of(&account_id)
+  }
+
+  fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+    StorageLedger::bounds()
+  }
+}
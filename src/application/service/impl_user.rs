@@ -8,6 +8,7 @@ use near_sdk::{env, near_bindgen};
 /// Implement function for user
 impl ImplUser for ELearningContract {
   /// Create a user
+  #[payable]
   fn create_user(
     &mut self,
     nickname: Option<String>,
@@ -15,8 +16,10 @@ impl ImplUser for ELearningContract {
     last_name: Option<String>,
     bio: Option<String>,
   ) {
+    let initial_storage = env::storage_usage();
+
     // Check User has exist
-    let user_id = env::signer_account_id();
+    let user_id = env::predecessor_account_id();
     assert!(!self.user_metadata_by_id.contains_key(&user_id), "User has already exists");
     let new_nickname = if let Some(value) = nickname { value } else { user_id.to_string() };
 
@@ -35,6 +38,8 @@ impl ImplUser for ELearningContract {
       updated_at: env::block_timestamp_ms(),
       courses_owned: 0,
       students: 0,
+      suspended: false,
+      managed_by: None,
     };
 
     // Create a Json of user
@@ -51,29 +56,12 @@ impl ImplUser for ELearningContract {
 
     // Storage user_id in system contract
     self.subscriber_users.insert(&user_id);
-  }
-
-  /// Update the role
-  fn update_role(&mut self) -> JsonUser {
-    // Only Owned has access
-    let user_id = env::signer_account_id();
-    assert!(self.user_metadata_by_id.contains_key(&user_id), "You don't have access!");
-
-    // Check user had the resume
-    let mut user = self.user_metadata_by_id.get(&user_id).unwrap();
-    assert!(user.metadata.role == Roles::Subscriber, "You already is Instructor");
-    assert!(user.metadata.resume.is_some(), "You must upload your resume!");
-
-    // Change user's role then storage
-    user.metadata.role = Roles::Instructor;
-    self.user_metadata_by_id.insert(&user_id, &user);
-    self.intructor_users.insert(&user_id);
 
-    // return
-    user
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
   }
 
   /// Update user information
+  #[payable]
   fn update_user_information(
     &mut self,
     nickname: Option<String>,
@@ -83,10 +71,13 @@ impl ImplUser for ELearningContract {
     avatar: Option<String>,
     resume: Option<String>,
   ) -> JsonUser {
+    let initial_storage = env::storage_usage();
+
     // Check access
-    assert!(self.user_metadata_by_id.contains_key(&env::signer_account_id()), "You don't have access");
+    assert!(self.user_metadata_by_id.contains_key(&env::predecessor_account_id()), "You don't have access");
+    self.internal_assert_not_suspended(&env::predecessor_account_id());
 
-    let mut user = self.user_metadata_by_id.get(&env::signer_account_id()).unwrap();
+    let mut user = self.user_metadata_by_id.get(&env::predecessor_account_id()).unwrap();
 
     // Check attribute. If it have some -> update
     if let Some(n) = nickname {
@@ -116,7 +107,8 @@ impl ImplUser for ELearningContract {
     user.metadata.updated_at = env::block_timestamp_ms();
 
     // Storage the change
-    self.user_metadata_by_id.insert(&env::signer_account_id(), &user);
+    self.user_metadata_by_id.insert(&env::predecessor_account_id(), &user);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
 
     // Return
     user
@@ -153,12 +145,6 @@ impl ImplUser for ELearningContract {
 
   /// Get information of user
   fn get_user_metadata_by_user_id(&self, user_id: &UserId) -> Option<JsonUser> {
-    if let Some(metadata) = self.user_metadata_by_id.get(user_id) {
-      Some(metadata)
-    } else {
-      None
-    }
+    self.user_metadata_by_id.get(user_id)
   }
-
-  fn create_pool() {}
 }
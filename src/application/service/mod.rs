@@ -1,4 +1,10 @@
+pub mod impl_admin;
 pub mod impl_certificate;
 pub mod impl_course;
+pub mod impl_fungible_token;
+pub mod impl_instructor;
+pub mod impl_nft;
+pub mod impl_pool;
 pub mod impl_skill;
+pub mod impl_storage;
 pub mod impl_user;
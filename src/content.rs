@@ -0,0 +1,76 @@
+//! References to content stored off chain. Works and courses keep a `ContentRef` instead of the content
+//! itself, so large files (images, videos, documents) never go into contract storage.
+
+use near_sdk::{
+  base64,
+  borsh::{self, BorshDeserialize, BorshSerialize},
+  env,
+  json_types::Base58CryptoHash,
+  serde::{Deserialize, Serialize},
+};
+
+/// URI schemes accepted for new content: IPFS, Arweave and HTTP(S).
+pub const CONTENT_URI_SCHEMES: [&str; 4] = ["ipfs://", "ar://", "https://", "http://"];
+
+/// Longest accepted `uri`.
+pub const MAX_CONTENT_URI_LEN: usize = 512;
+
+/// Longest accepted `mime`.
+pub const MAX_CONTENT_MIME_LEN: usize = 128;
+
+/// Largest accepted `size`, 100 MiB.
+pub const MAX_CONTENT_SIZE: u64 = 100 * 1024 * 1024;
+
+/// The `ContentRef` struct points to content stored off chain and pins it by hash.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContentRef {
+  /// Location of the content, e.g. "ipfs://<cid>", "ar://<tx id>" or "https://...".
+  pub uri: String,
+
+  /// Base58 sha256 of the bytes stored at `uri`. Deserialization rejects anything that is not 32 bytes.
+  pub hash: Base58CryptoHash,
+
+  /// Size of the content in bytes.
+  pub size: u64,
+
+  /// MIME type of the content, e.g. "image/png".
+  pub mime: String,
+}
+
+impl ContentRef {
+  /// Content that was stored on chain before `ContentRef` existed. It stays on chain as a `data:` URI.
+  pub fn from_legacy(content: &str) -> Self {
+    Self {
+      uri: format!("data:text/plain;base64,{}", base64::encode(content)),
+      hash: env::sha256_array(content.as_bytes()).into(),
+      size: content.len() as u64,
+      mime: "text/plain".to_string(),
+    }
+  }
+
+  /// Panic unless the reference has a supported URI, a size within the limit and a `type/subtype` MIME type.
+  pub fn assert_valid(&self) {
+    assert!(self.uri.len() <= MAX_CONTENT_URI_LEN, "Content uri is too long");
+    assert!(!self.uri.chars().any(char::is_whitespace), "Content uri must not contain whitespace");
+    assert!(
+      CONTENT_URI_SCHEMES.iter().any(|scheme| self.uri.len() > scheme.len() && self.uri.starts_with(scheme)),
+      "Content uri must start with ipfs://, ar://, https:// or http://"
+    );
+
+    assert!(self.size > 0, "Content size must be positive");
+    assert!(self.size <= MAX_CONTENT_SIZE, "Content is larger than the {} bytes limit", MAX_CONTENT_SIZE);
+
+    assert!(self.mime.len() <= MAX_CONTENT_MIME_LEN, "Content mime type is too long");
+    let valid_mime = match self.mime.split_once('/') {
+      Some((kind, subtype)) => {
+        !kind.is_empty()
+          && !subtype.is_empty()
+          && !subtype.contains('/')
+          && self.mime.chars().all(|c| c.is_ascii_graphic())
+      },
+      None => false,
+    };
+    assert!(valid_mime, "Content mime type must look like type/subtype");
+  }
+}
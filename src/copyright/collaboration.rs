@@ -0,0 +1,341 @@
+//! Invitations to collaborate on a work.
+//!
+//! Nobody becomes a collaborator without agreeing to it. `create_work` and an `AddCollaborators` proposal
+//! invite accounts, which then call `accept_collaboration` or `decline_collaboration` before the invitation
+//! expires. Ratios that name an invited account stay pending, and the work earns by its previous ratios,
+//! until every account they name has accepted.
+//!
+//! A collaborator leaves with `leave_work`, or is removed by a `RemoveCollaborator` proposal. Its ratio is
+//! shared as the policy of the work says. It keeps its royalty balance and stays an authorized user of the
+//! work.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Timestamp};
+
+use crate::event::{
+  CollaborationInviteLog, CollaborationResponseLog, EventLog, EventLogVariant, WorkTransferCancelLog,
+};
+
+use super::{check_valid_ratios, is_member, Contract, ContractExt, RatioRedistribution, Work, WorkId};
+
+/// How long an invitation can be accepted, 30 days in milliseconds.
+const INVITATION_PERIOD: u64 = 30 * 24 * 60 * 60 * 1_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Invitation {
+  pub account_id: AccountId,
+  pub inviter_id: AccountId,
+  /// Block time in milliseconds.
+  pub expires_at: Timestamp,
+}
+
+impl Invitation {
+  fn is_expired(&self) -> bool {
+    env::block_timestamp_ms() >= self.expires_at
+  }
+}
+
+#[near_bindgen]
+impl Contract {
+  /// Become a collaborator of a work the caller was invited to. Pending ratios become active once everyone
+  /// they name has accepted. A pending transfer offer is called off, since the new collaborator did not agree
+  /// on it.
+  #[payable]
+  pub fn accept_collaboration(&mut self, work_id: WorkId) {
+    let initial_storage = env::storage_usage();
+    let account_id = env::predecessor_account_id();
+    let mut work = self.works.get(&work_id).expect("There is no work");
+    let invitation = take_invitation(&mut work, &account_id);
+    assert!(!invitation.is_expired(), "The invitation has expired");
+
+    work.collaborators.push(account_id.clone());
+    if work.pending_transfer.take().is_some() {
+      EventLog::copyright(EventLogVariant::WorkTransferCancel(vec![WorkTransferCancelLog {
+        work_id: work_id.clone(),
+        cancelled_by: account_id.clone(),
+      }]))
+      .emit();
+    }
+    if work.pending_ratios.as_ref().map(|ratios| ratios_are_accepted(&work, ratios)).unwrap_or(false) {
+      work.ratios = work.pending_ratios.take();
+      work.updated_date = env::block_timestamp_ms();
+      self.internal_record_revision(&work, vec!["ratios".to_string()], account_id.clone(), work.updated_date);
+    }
+    self.internal_save_work(&work);
+
+    EventLog::copyright(EventLogVariant::CollaborationAccept(vec![CollaborationResponseLog { work_id, account_id }]))
+      .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  /// Turn down an invitation to a work. Pending ratios that name the caller are dropped.
+  #[payable]
+  pub fn decline_collaboration(&mut self, work_id: WorkId) {
+    let initial_storage = env::storage_usage();
+    let account_id = env::predecessor_account_id();
+    let mut work = self.works.get(&work_id).expect("There is no work");
+    take_invitation(&mut work, &account_id);
+
+    if work.pending_ratios.as_ref().map(|ratios| ratios.iter().any(|(id, _)| id == &account_id)).unwrap_or(false) {
+      work.pending_ratios = None;
+    }
+    self.internal_save_work(&work);
+
+    EventLog::copyright(EventLogVariant::CollaborationDecline(vec![CollaborationResponseLog { work_id, account_id }]))
+      .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  /// Stop collaborating on a work. Only a collaborator can call this function; the author transfers the work
+  /// instead.
+  #[payable]
+  pub fn leave_work(&mut self, work_id: WorkId) {
+    let initial_storage = env::storage_usage();
+    let account_id = env::predecessor_account_id();
+    let mut work = self.works.get(&work_id).expect("There is no work");
+    assert!(work.collaborators.contains(&account_id), "Only a collaborator can leave a work");
+
+    let updated_fields = remove_collaborator(&mut work, &account_id);
+    work.updated_date = env::block_timestamp_ms();
+    self.internal_save_work(&work);
+    if updated_fields.iter().any(|field| field == "ratios") {
+      self.internal_record_revision(&work, updated_fields, account_id.clone(), work.updated_date);
+    }
+
+    EventLog::copyright(EventLogVariant::CollaborationLeave(vec![CollaborationResponseLog { work_id, account_id }]))
+      .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  /// Invitations of a work that can still be accepted.
+  pub fn get_invitations(&self, work_id: WorkId) -> Vec<Invitation> {
+    self
+      .works
+      .get(&work_id)
+      .map(|work| work.invitations.into_iter().filter(|invitation| !invitation.is_expired()).collect())
+      .unwrap_or_default()
+  }
+}
+
+impl Contract {
+  /// Invite accounts to collaborate on a work. An expired invitation is renewed.
+  pub(crate) fn internal_invite(&mut self, work: &mut Work, account_ids: Vec<AccountId>, inviter_id: &AccountId) {
+    let expires_at = env::block_timestamp_ms() + INVITATION_PERIOD;
+    let mut invite_logs = Vec::new();
+
+    for account_id in account_ids {
+      assert_invitable(work, &account_id);
+      work.invitations.retain(|invitation| invitation.account_id != account_id);
+      work.invitations.push(Invitation { account_id: account_id.clone(), inviter_id: inviter_id.clone(), expires_at });
+      invite_logs.push(CollaborationInviteLog {
+        work_id: work.id.clone(),
+        inviter_id: inviter_id.clone(),
+        invitee_id: account_id,
+        expires_at,
+      });
+    }
+
+    if !invite_logs.is_empty() {
+      EventLog::copyright(EventLogVariant::CollaborationInvite(invite_logs)).emit();
+    }
+  }
+
+  /// Set the ratios of a work, or keep them pending while they name an invited account. Returns whether they
+  /// are active.
+  pub(crate) fn internal_set_ratios(&mut self, work: &mut Work, ratios: Vec<(AccountId, u32)>) -> bool {
+    assert_valid_ratio_parties(work, &ratios);
+
+    if ratios_are_accepted(work, &ratios) {
+      work.ratios = Some(ratios);
+      work.pending_ratios = None;
+      true
+    } else {
+      work.pending_ratios = Some(ratios);
+      false
+    }
+  }
+}
+
+/// Whether `account_id` can be invited to the work: it is not a member and has no pending invitation.
+pub(crate) fn check_invitable(work: &Work, account_id: &AccountId) -> Result<(), String> {
+  if is_member(work, account_id) {
+    return Err(format!("{} is already a member of this work", account_id));
+  }
+  if work.invitations.iter().any(|invitation| &invitation.account_id == account_id && !invitation.is_expired()) {
+    return Err(format!("{} is already invited to this work", account_id));
+  }
+  Ok(())
+}
+
+pub(crate) fn assert_invitable(work: &Work, account_id: &AccountId) {
+  if let Err(error) = check_invitable(work, account_id) {
+    env::panic_str(&error);
+  }
+}
+
+/// Whether the ratios are valid and name only members and invited accounts.
+pub(crate) fn check_ratio_parties(work: &Work, ratios: &[(AccountId, u32)]) -> Result<(), String> {
+  check_valid_ratios(ratios)?;
+  for (account_id, _) in ratios {
+    if !is_member(work, account_id) && !work.invitations.iter().any(|invitation| &invitation.account_id == account_id) {
+      return Err(format!("{} is neither a member of this work nor invited to it", account_id));
+    }
+  }
+  Ok(())
+}
+
+pub(crate) fn assert_valid_ratio_parties(work: &Work, ratios: &[(AccountId, u32)]) {
+  if let Err(error) = check_ratio_parties(work, ratios) {
+    env::panic_str(&error);
+  }
+}
+
+/// Drop a collaborator from a work, share its ratio as the policy of the work says and keep it as an
+/// authorized user. Pending ratios that name it can no longer become active, so they are dropped. Returns the
+/// changed fields.
+pub(crate) fn remove_collaborator(work: &mut Work, account_id: &AccountId) -> Vec<String> {
+  let mut updated_fields = vec!["collaborators".to_string()];
+  work.collaborators.retain(|id| id != account_id);
+  if !work.authorized_users.contains(account_id) {
+    work.authorized_users.push(account_id.clone());
+  }
+  if work.pending_ratios.as_ref().map(|ratios| ratios.iter().any(|(id, _)| id == account_id)).unwrap_or(false) {
+    work.pending_ratios = None;
+  }
+
+  if let Some(ratios) = work.ratios.as_mut() {
+    if let Some(index) = ratios.iter().position(|(id, _)| id == account_id) {
+      let (_, ratio) = ratios.remove(index);
+      redistribute(ratios, ratio, &work.author.id, work.policy.redistribution);
+      updated_fields.push("ratios".to_string());
+    }
+  }
+  updated_fields
+}
+
+/// Add `ratio` to the ratios by `rule`. What is left after rounding goes to the author.
+fn redistribute(ratios: &mut Vec<(AccountId, u32)>, ratio: u32, author_id: &AccountId, rule: RatioRedistribution) {
+  let rest: u32 = ratios.iter().map(|(_, ratio)| *ratio).sum();
+  let mut left = ratio;
+  if rule == RatioRedistribution::Proportional && rest > 0 {
+    for (_, member_ratio) in ratios.iter_mut() {
+      let share = (u64::from(ratio) * u64::from(*member_ratio) / u64::from(rest)) as u32;
+      *member_ratio += share;
+      left -= share;
+    }
+  }
+
+  match ratios.iter_mut().find(|(id, _)| id == author_id) {
+    Some((_, author_ratio)) => *author_ratio += left,
+    None if left > 0 => ratios.push((author_id.clone(), left)),
+    None => (),
+  }
+}
+
+/// Whether everyone the ratios name is a member of the work.
+pub(super) fn ratios_are_accepted(work: &Work, ratios: &[(AccountId, u32)]) -> bool {
+  ratios.iter().all(|(account_id, _)| is_member(work, account_id))
+}
+
+/// Remove the invitation of `account_id` from the work and return it.
+fn take_invitation(work: &mut Work, account_id: &AccountId) -> Invitation {
+  let index = work
+    .invitations
+    .iter()
+    .position(|invitation| &invitation.account_id == account_id)
+    .expect("There is no invitation to this work");
+  work.invitations.remove(index)
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::json_types::U128;
+  use near_sdk::test_utils::accounts;
+  use near_sdk::ONE_NEAR;
+
+  use crate::test_utils::{create_song, set_caller_at};
+  use super::*;
+  use crate::Copyright;
+
+  /// accounts(1) wrote a work and invited accounts(2) and accounts(3) with a 50/25/25 split
+  fn setup() -> (Contract, WorkId) {
+    set_caller_at(accounts(1), ONE_NEAR, 0);
+    let mut contract = Contract::new(accounts(0));
+    let collaborators = Some(vec![accounts(2), accounts(3)]);
+    let ratios = Some(vec![(accounts(1), 5_000), (accounts(2), 2_500), (accounts(3), 2_500)]);
+    let work = create_song(&mut contract, collaborators, None, ratios);
+    (contract, work.id)
+  }
+
+  #[test]
+  fn ratios_become_active_once_everyone_accepts() {
+    let (mut contract, work_id) = setup();
+    let work = contract.works.get(&work_id).unwrap();
+    assert!(work.collaborators.is_empty());
+    assert_eq!(work.ratios, None);
+    assert_eq!(contract.get_invitations(work_id.clone()).len(), 2);
+
+    set_caller_at(accounts(2), ONE_NEAR, 1_000);
+    contract.accept_collaboration(work_id.clone());
+    assert_eq!(contract.works.get(&work_id).unwrap().ratios, None);
+
+    set_caller_at(accounts(3), ONE_NEAR, 2_000);
+    contract.accept_collaboration(work_id.clone());
+    let work = contract.works.get(&work_id).unwrap();
+    assert_eq!(work.collaborators, vec![accounts(2), accounts(3)]);
+    assert_eq!(work.ratios, Some(vec![(accounts(1), 5_000), (accounts(2), 2_500), (accounts(3), 2_500)]));
+    assert_eq!(work.pending_ratios, None);
+    assert!(contract.get_invitations(work_id).is_empty());
+  }
+
+  #[test]
+  fn declining_drops_the_pending_ratios() {
+    let (mut contract, work_id) = setup();
+    set_caller_at(accounts(3), ONE_NEAR, 0);
+    contract.decline_collaboration(work_id.clone());
+
+    let work = contract.works.get(&work_id).unwrap();
+    assert_eq!(work.pending_ratios, None);
+    assert_eq!(contract.get_invitations(work_id).len(), 1);
+  }
+
+  #[test]
+  fn leaving_collaborator_keeps_its_royalties_and_access() {
+    let (mut contract, work_id) = setup();
+    for index in 2..4 {
+      set_caller_at(accounts(index), ONE_NEAR, 0);
+      contract.accept_collaboration(work_id.clone());
+    }
+    let work = contract.works.get(&work_id).unwrap();
+    contract.internal_distribute_funds(&work, 100);
+
+    set_caller_at(accounts(3), ONE_NEAR, 1_000);
+    contract.leave_work(work_id.clone());
+
+    let work = contract.works.get(&work_id).unwrap();
+    assert_eq!(work.collaborators, vec![accounts(2)]);
+    assert_eq!(work.ratios, Some(vec![(accounts(1), 6_667), (accounts(2), 3_333)]));
+    assert!(work.authorized_users.contains(&accounts(3)));
+
+    // What it earned before leaving stays, nothing more comes
+    contract.internal_distribute_funds(&work, 100);
+    assert_eq!(contract.get_royalties(accounts(3))[0].amount, U128(25));
+    assert_eq!(
+      contract.get_work_revisions(work_id, None, None).last().unwrap().changed_fields,
+      ["collaborators", "ratios"]
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "The invitation has expired")]
+  fn expired_invitation_can_not_be_accepted() {
+    let (mut contract, work_id) = setup();
+    set_caller_at(accounts(2), ONE_NEAR, INVITATION_PERIOD);
+    contract.accept_collaboration(work_id);
+  }
+}
@@ -0,0 +1,127 @@
+//! Work fees paid in NEP-141 tokens.
+//!
+//! The owner keeps a whitelist of token contracts, and the members of a work can have its fee paid in one of
+//! them with a `ChangeFeeToken` proposal.
+//! A buyer then calls `ft_transfer_call` on that token with this `msg`:
+//!
+//! `{"action": "buy_access", "work_id": "<work id>", "public_key": "<base64 X25519 key>"}`
+
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::serde::Deserialize;
+use near_sdk::serde_json;
+use near_sdk::{env, near_bindgen, AccountId, PromiseOrValue};
+
+use crate::fungible_token::FungibleTokenReceiver;
+
+use super::{is_member, Contract, ContractExt, WorkId};
+
+/// Purchases a `msg` of `ft_transfer_call` can ask for.
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde", tag = "action", rename_all = "snake_case")]
+enum TokenPurchase {
+  BuyAccess { work_id: WorkId, public_key: Base64VecU8 },
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+  fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+    let initial_storage = env::storage_usage();
+    let token_id = self.accepted_tokens.assert_caller_accepted();
+    let purchase: TokenPurchase = serde_json::from_str(&msg).expect("Invalid msg");
+
+    match purchase {
+      TokenPurchase::BuyAccess { work_id, public_key } => {
+        let work = self.works.get(&work_id).expect("There is no work");
+        assert!(work.fee_token.as_ref() == Some(&token_id), "The fee of this work is not paid in {}", token_id);
+        assert!(!is_member(&work, &sender_id), "Members of a work do not pay for it");
+        let fee = work.fee.0;
+        assert!(amount.0 >= fee, "Transfer at least {} to get access", fee);
+        self.internal_purchase_access(work, sender_id.clone(), public_key, U128(fee));
+
+        // The tokens pay the fee, so storage comes from the storage balance of the buyer
+        self.storage_balances.charge(&sender_id, initial_storage, 0);
+        PromiseOrValue::Value(U128(amount.0 - fee))
+      },
+    }
+  }
+}
+
+#[near_bindgen]
+impl Contract {
+  /// Accept fees in a token contract. Only the owner can call this function.
+  #[payable]
+  pub fn add_accepted_token(&mut self, token_id: AccountId) -> bool {
+    let initial_storage = env::storage_usage();
+    self.assert_owner();
+    let changed = self.accepted_tokens.insert(&token_id);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    changed
+  }
+
+  /// Stop accepting a token contract for new fees. Only the owner can call this function.
+  #[payable]
+  pub fn remove_accepted_token(&mut self, token_id: AccountId) -> bool {
+    let initial_storage = env::storage_usage();
+    self.assert_owner();
+    let changed = self.accepted_tokens.remove(&token_id);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    changed
+  }
+
+  pub fn get_accepted_tokens(&self) -> Vec<AccountId> {
+    self.accepted_tokens.to_vec()
+  }
+
+  fn assert_owner(&self) {
+    assert_eq!(env::predecessor_account_id(), self.owner_id, "Only the owner can do this");
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::test_utils::accounts;
+  use near_sdk::ONE_NEAR;
+
+  use crate::test_utils::{create_song, set_caller, set_signer_and_caller};
+  use super::*;
+  use crate::storage::StorageManagement;
+  use crate::{Copyright, ProposalKind, RoyaltyBalance};
+
+  #[test]
+  fn buy_access_with_tokens() {
+    set_caller(accounts(0), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    contract.add_accepted_token(accounts(3));
+
+    // accounts(1) and accounts(4) share a work whose fee is 100 tokens of accounts(3)
+    let work = create_song(&mut contract, Some(vec![accounts(4)]), Some(U128(100)), None);
+    contract.propose(work.id.clone(), ProposalKind::ChangeFeeToken { fee_token: Some(accounts(3)) });
+    set_caller(accounts(4), ONE_NEAR);
+    contract.accept_collaboration(work.id.clone());
+
+    set_caller(accounts(2), ONE_NEAR);
+    contract.storage_deposit(None, None);
+
+    // The token contract calls back with the buyer as sender
+    set_signer_and_caller(accounts(2), accounts(3), 0);
+    let public_key = near_sdk::base64::encode([1; 32]);
+    let msg = format!(r#"{{"action":"buy_access","work_id":"{}","public_key":"{}"}}"#, work.id, public_key);
+    match contract.ft_on_transfer(accounts(2), U128(150), msg) {
+      PromiseOrValue::Value(unused) => assert_eq!(unused, U128(50)),
+      PromiseOrValue::Promise(_) => panic!("Expected the unused amount"),
+    }
+    let royalties = vec![RoyaltyBalance { token_id: Some(accounts(3)), amount: U128(50) }];
+    assert_eq!(contract.get_royalties(accounts(1)), royalties);
+    assert_eq!(contract.get_royalties(accounts(4)), royalties);
+    assert_eq!(contract.get_pending_content_keys(work.id, None, None), vec![(accounts(2), vec![1; 32].into())]);
+  }
+
+  #[test]
+  #[should_panic(expected = "The token charlie is not accepted")]
+  fn tokens_must_be_accepted() {
+    set_caller(accounts(0), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    set_caller(accounts(2), 0);
+    contract.ft_on_transfer(accounts(2), U128(150), "{}".to_string());
+  }
+}
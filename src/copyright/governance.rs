@@ -0,0 +1,584 @@
+//! Decisions the members of a work take together.
+//!
+//! The author or a collaborator proposes a change to a work, which counts as a yes vote of the proposer. The
+//! members then vote until the proposal expires. A proposal passes once enough members voted (the quorum) and
+//! enough of them approved (the threshold), both in basis points of the work's `GovernancePolicy`, and it is
+//! executed right away by the vote that made it pass. It is rejected once it can no longer pass, and it fails
+//! when it passes but the work changed so that it no longer applies.
+//!
+//! Every member weighs the same, unless the policy is weighted: then a vote weighs the ratio of the voter, in
+//! basis points of what the work earns.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::Vector;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Timestamp};
+
+use crate::event::{
+  EventLog, EventLogVariant, ProposalCreateLog, ProposalResolveLog, WorkTransferOfferLog, WorkUpdateLog, WorkVoteLog,
+};
+
+use super::collaboration::{check_invitable, check_ratio_parties, remove_collaborator};
+use super::{is_member, Contract, ContractExt, StorageKey, TransferOffer, Vote, Work, WorkId, FULL_RATIO};
+
+pub type ProposalId = u64;
+
+/// How the members of a work decide on proposals.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GovernancePolicy {
+  /// Share of the members that must vote, in basis points.
+  pub quorum: u32,
+  /// Share of the votes that must approve, in basis points. A proposal passes with more than this share.
+  pub threshold: u32,
+  /// How long a proposal stays open, in milliseconds.
+  pub voting_period: u64,
+  /// Weigh every vote by the ratio of the voter instead of counting one vote per member. Members weigh the
+  /// same when the work has no ratios.
+  pub weighted: bool,
+  /// Who gets the ratio of a collaborator that leaves or is removed.
+  pub redistribution: RatioRedistribution,
+}
+
+/// How the ratio of a departing collaborator is shared by the members who stay.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum RatioRedistribution {
+  /// In proportion to the ratios of the members who stay. The rounding remainder goes to the author.
+  Proportional,
+  /// All of it to the author.
+  Author,
+}
+
+impl Default for GovernancePolicy {
+  /// Three quarters of the members vote, a majority of them approves, within 7 days, one vote per member. The
+  /// ratio of a departing collaborator is shared in proportion.
+  fn default() -> Self {
+    Self {
+      quorum: 7_500,
+      threshold: 5_000,
+      voting_period: 7 * 24 * 60 * 60 * 1_000,
+      weighted: false,
+      redistribution: RatioRedistribution::Proportional,
+    }
+  }
+}
+
+impl GovernancePolicy {
+  fn assert_valid(&self) {
+    assert!(self.quorum <= FULL_RATIO, "The quorum can not be more than {} basis points", FULL_RATIO);
+    assert!(self.threshold < FULL_RATIO, "The threshold must be less than {} basis points", FULL_RATIO);
+    assert!(self.voting_period > 0, "The voting period can not be empty");
+  }
+}
+
+/// A change to a work that the members vote on.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde", tag = "type", rename_all = "snake_case")]
+pub enum ProposalKind {
+  DeleteWork,
+  /// Invite accounts to collaborate. They join once they accept.
+  AddCollaborators {
+    collaborators: Vec<AccountId>,
+  },
+  /// Drop a collaborator. Its ratio is shared as the policy says.
+  RemoveCollaborator {
+    collaborator: AccountId,
+  },
+  /// Ratios naming an invited account stay pending until it accepts.
+  ChangeRatios {
+    ratios: Vec<(AccountId, u32)>,
+  },
+  ChangeFee {
+    fee: U128,
+  },
+  /// Offer the work to a registered author, who becomes its author with `accept_work_transfer`. A
+  /// collaborator trades places with the author.
+  TransferOwnership {
+    new_owner: AccountId,
+  },
+  ChangePolicy {
+    policy: GovernancePolicy,
+  },
+  /// Have the fee paid in an accepted token contract, or in NEAR when None.
+  ChangeFeeToken {
+    fee_token: Option<AccountId>,
+  },
+}
+
+impl ProposalKind {
+  fn name(&self) -> &'static str {
+    match self {
+      ProposalKind::DeleteWork => "delete_work",
+      ProposalKind::AddCollaborators { .. } => "add_collaborators",
+      ProposalKind::RemoveCollaborator { .. } => "remove_collaborator",
+      ProposalKind::ChangeRatios { .. } => "change_ratios",
+      ProposalKind::ChangeFee { .. } => "change_fee",
+      ProposalKind::TransferOwnership { .. } => "transfer_ownership",
+      ProposalKind::ChangePolicy { .. } => "change_policy",
+      ProposalKind::ChangeFeeToken { .. } => "change_fee_token",
+    }
+  }
+}
+
+/// `Expired` is never stored: a proposal still in progress after `expires_at` is reported as expired.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalStatus {
+  InProgress,
+  Approved,
+  Rejected,
+  Expired,
+  /// Approved, but the work changed so that the proposal no longer applies. Nothing was executed.
+  Failed,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Proposal {
+  pub(super) id: ProposalId,
+  pub(super) work_id: WorkId,
+  pub(super) proposer: AccountId,
+  pub(super) kind: ProposalKind,
+  pub(super) votes: Vec<Vote>,
+  pub(super) status: ProposalStatus,
+  /// Block time in milliseconds.
+  pub(super) submitted_at: Timestamp,
+  pub(super) expires_at: Timestamp,
+}
+
+impl Proposal {
+  fn current_status(&self) -> ProposalStatus {
+    if self.status == ProposalStatus::InProgress && env::block_timestamp_ms() >= self.expires_at {
+      ProposalStatus::Expired
+    } else {
+      self.status
+    }
+  }
+
+  /// Count the votes of the current members of `work`.
+  fn tally(&self, work: &Work) -> ProposalTally {
+    let mut tally = ProposalTally {
+      status: self.status,
+      yes_weight: 0,
+      no_weight: 0,
+      total_weight: members(work).map(|account_id| vote_weight(work, account_id)).sum(),
+      quorum_reached: false,
+      time_remaining: self.expires_at.saturating_sub(env::block_timestamp_ms()),
+    };
+    for vote in self.votes.iter().filter(|vote| is_member(work, &vote.voter)) {
+      if vote.decision {
+        tally.yes_weight += vote_weight(work, &vote.voter);
+      } else {
+        tally.no_weight += vote_weight(work, &vote.voter);
+      }
+    }
+
+    let policy = &work.policy;
+    let full = u64::from(FULL_RATIO);
+    let cast = tally.yes_weight + tally.no_weight;
+    tally.quorum_reached = cast * full >= u64::from(policy.quorum) * tally.total_weight;
+
+    if self.status != ProposalStatus::InProgress {
+      return tally;
+    }
+    tally.status = if tally.quorum_reached && tally.yes_weight * full > u64::from(policy.threshold) * cast {
+      ProposalStatus::Approved
+    } else if (tally.total_weight - tally.no_weight) * full <= u64::from(policy.threshold) * tally.total_weight {
+      // Not even the members who did not vote yet can make it pass
+      ProposalStatus::Rejected
+    } else if tally.time_remaining == 0 {
+      ProposalStatus::Expired
+    } else {
+      ProposalStatus::InProgress
+    };
+    tally
+  }
+}
+
+/// Votes on a proposal, weighed by the policy of the work. Only the votes of the current members count.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalTally {
+  pub status: ProposalStatus,
+  pub yes_weight: u64,
+  pub no_weight: u64,
+  /// Weight of all the members, voting or not.
+  pub total_weight: u64,
+  pub quorum_reached: bool,
+  /// Milliseconds until the proposal expires.
+  pub time_remaining: u64,
+}
+
+fn members(work: &Work) -> impl Iterator<Item = &AccountId> {
+  std::iter::once(&work.author.id).chain(work.collaborators.iter())
+}
+
+/// Weight of the vote of a member: its ratio when the policy is weighted and the work has ratios, 1 otherwise.
+fn vote_weight(work: &Work, account_id: &AccountId) -> u64 {
+  match (&work.ratios, work.policy.weighted) {
+    (Some(ratios), true) => ratios.iter().filter(|(id, _)| id == account_id).map(|(_, ratio)| u64::from(*ratio)).sum(),
+    _ => 1,
+  }
+}
+
+#[near_bindgen]
+impl Contract {
+  /// Propose a change to a work, with a yes vote of the caller. Only the author and the collaborators can
+  /// call this function.
+  #[payable]
+  pub fn propose(&mut self, work_id: WorkId, kind: ProposalKind) -> ProposalId {
+    let initial_storage = env::storage_usage();
+    let proposer = env::predecessor_account_id();
+    let work = self.works.get(&work_id).expect("There is no work");
+    assert!(is_member(&work, &proposer), "Only the members of a work can propose");
+    self.assert_applicable(&work, &kind);
+
+    let id = self.next_proposal_id;
+    self.next_proposal_id += 1;
+    let submitted_at = env::block_timestamp_ms();
+    let proposal = Proposal {
+      id,
+      work_id: work_id.clone(),
+      proposer: proposer.clone(),
+      kind,
+      votes: Vec::new(),
+      status: ProposalStatus::InProgress,
+      submitted_at,
+      expires_at: submitted_at + work.policy.voting_period,
+    };
+
+    let mut proposal_ids = self.proposals_per_work.get(&work_id).unwrap_or_else(|| {
+      Vector::new(
+        StorageKey::ProposalsPerWorkInner { work_id_hash: env::sha256_array(work_id.as_bytes()) }.try_to_vec().unwrap(),
+      )
+    });
+    proposal_ids.push(&id);
+    self.proposals_per_work.insert(&work_id, &proposal_ids);
+
+    EventLog::copyright(EventLogVariant::ProposalCreate(vec![ProposalCreateLog {
+      work_id,
+      proposal_id: id,
+      proposer_id: proposer.clone(),
+      kind: proposal.kind.name().to_string(),
+    }]))
+    .emit();
+
+    self.internal_vote(work, proposal, proposer, true);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    id
+  }
+
+  /// Vote on a proposal in progress, and execute it if this vote makes it pass. Only the author and the
+  /// collaborators of the work can vote, once.
+  #[payable]
+  pub fn vote_on_proposal(&mut self, proposal_id: ProposalId, approve: bool) -> ProposalStatus {
+    let initial_storage = env::storage_usage();
+    let voter = env::predecessor_account_id();
+    let proposal = self.proposals.get(&proposal_id).expect("There is no proposal");
+    let work = self.works.get(&proposal.work_id).expect("There is no work");
+    assert!(is_member(&work, &voter), "Only the members of a work can vote");
+    match proposal.current_status() {
+      ProposalStatus::InProgress => (),
+      ProposalStatus::Expired => env::panic_str("The proposal has expired"),
+      _ => env::panic_str("The proposal is already decided"),
+    }
+    assert!(!proposal.votes.iter().any(|vote| vote.voter == voter), "Already voted");
+
+    let status = self.internal_vote(work, proposal, voter, approve);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    status
+  }
+
+  pub fn get_proposal(&self, proposal_id: ProposalId) -> Option<Proposal> {
+    self.proposals.get(&proposal_id).map(|mut proposal| {
+      proposal.status = proposal.current_status();
+      proposal
+    })
+  }
+
+  /// Weight of the votes on a proposal so far. None when the work was deleted.
+  pub fn get_proposal_tally(&self, proposal_id: ProposalId) -> Option<ProposalTally> {
+    let proposal = self.proposals.get(&proposal_id)?;
+    let work = self.works.get(&proposal.work_id)?;
+    Some(proposal.tally(&work))
+  }
+
+  pub fn get_proposals_per_work(&self, work_id: WorkId, from_index: Option<u32>, limit: Option<u32>) -> Vec<Proposal> {
+    let proposal_ids = if let Some(ids) = self.proposals_per_work.get(&work_id) { ids } else { return vec![] };
+
+    proposal_ids
+      .iter()
+      .skip(from_index.unwrap_or(0) as usize)
+      .take(limit.unwrap_or(20) as usize)
+      .filter_map(|proposal_id| self.get_proposal(proposal_id))
+      .collect()
+  }
+}
+
+impl Contract {
+  /// Record a vote, then execute the proposal if it passed. Returns the new status.
+  fn internal_vote(&mut self, work: Work, mut proposal: Proposal, voter: AccountId, decision: bool) -> ProposalStatus {
+    proposal.votes.push(Vote { voter: voter.clone(), decision, timestamp: env::block_timestamp_ms() });
+    proposal.status = proposal.tally(&work).status;
+    // The work may have changed since the proposal was made
+    if proposal.status == ProposalStatus::Approved && self.check_applicable(&work, &proposal.kind).is_err() {
+      proposal.status = ProposalStatus::Failed;
+    }
+    self.proposals.insert(&proposal.id, &proposal);
+
+    EventLog::copyright(EventLogVariant::WorkVote(vec![WorkVoteLog {
+      work_id: work.id.clone(),
+      proposal_id: proposal.id,
+      voter_id: voter,
+      decision,
+    }]))
+    .emit();
+
+    if proposal.status != ProposalStatus::InProgress {
+      EventLog::copyright(EventLogVariant::ProposalResolve(vec![ProposalResolveLog {
+        work_id: work.id.clone(),
+        proposal_id: proposal.id,
+        approved: proposal.status == ProposalStatus::Approved,
+      }]))
+      .emit();
+    }
+
+    if proposal.status == ProposalStatus::Approved {
+      self.internal_execute_proposal(work, &proposal);
+    }
+    proposal.status
+  }
+
+  /// Whether `kind` can be applied to the work as it is now.
+  fn check_applicable(&self, work: &Work, kind: &ProposalKind) -> Result<(), String> {
+    match kind {
+      ProposalKind::DeleteWork | ProposalKind::ChangeFee { .. } => (),
+      ProposalKind::AddCollaborators { collaborators } => {
+        if collaborators.is_empty() {
+          return Err("There is no collaborator to add".to_string());
+        }
+        for (index, collaborator) in collaborators.iter().enumerate() {
+          check_invitable(work, collaborator)?;
+          if collaborators[..index].contains(collaborator) {
+            return Err(format!("{} is invited twice", collaborator));
+          }
+        }
+      },
+      ProposalKind::RemoveCollaborator { collaborator } => {
+        if !work.collaborators.contains(collaborator) {
+          return Err(format!("{} is not a collaborator of this work", collaborator));
+        }
+      },
+      ProposalKind::ChangeRatios { ratios } => check_ratio_parties(work, ratios)?,
+      ProposalKind::TransferOwnership { new_owner } => {
+        if new_owner == &work.author.id {
+          return Err(format!("{} is already the author of this work", new_owner));
+        }
+        if self.authors.get(new_owner).is_none() {
+          return Err("The new owner is not an author".to_string());
+        }
+      },
+      ProposalKind::ChangePolicy { policy } => policy.assert_valid(),
+      ProposalKind::ChangeFeeToken { fee_token: Some(token_id) } => {
+        if !self.accepted_tokens.contains(token_id) {
+          return Err(format!("The token {} is not accepted", token_id));
+        }
+      },
+      ProposalKind::ChangeFeeToken { fee_token: None } => (),
+    }
+    Ok(())
+  }
+
+  fn assert_applicable(&self, work: &Work, kind: &ProposalKind) {
+    if let Err(error) = self.check_applicable(work, kind) {
+      env::panic_str(&error);
+    }
+  }
+
+  fn internal_execute_proposal(&mut self, mut work: Work, proposal: &Proposal) {
+    let mut updated_fields = Vec::new();
+
+    match proposal.kind.clone() {
+      ProposalKind::DeleteWork => {
+        self.internal_delete_work(&work);
+        return;
+      },
+      ProposalKind::TransferOwnership { new_owner } => {
+        work.pending_transfer = Some(TransferOffer { new_owner: new_owner.clone(), price: None, memo: None });
+        self.internal_save_work(&work);
+        EventLog::copyright(EventLogVariant::WorkTransferOffer(vec![WorkTransferOfferLog {
+          work_id: work.id.clone(),
+          owner_id: work.author.id.clone(),
+          new_owner_id: new_owner,
+          price: None,
+        }]))
+        .emit();
+        return;
+      },
+      ProposalKind::ChangePolicy { policy } => {
+        work.policy = policy;
+        self.internal_save_work(&work);
+        return;
+      },
+      ProposalKind::AddCollaborators { collaborators } => {
+        self.internal_invite(&mut work, collaborators, &proposal.proposer);
+        self.internal_save_work(&work);
+        return;
+      },
+      ProposalKind::RemoveCollaborator { collaborator } => {
+        updated_fields = remove_collaborator(&mut work, &collaborator);
+      },
+      ProposalKind::ChangeRatios { ratios } => {
+        if self.internal_set_ratios(&mut work, ratios) {
+          updated_fields.push("ratios".to_string());
+        } else {
+          updated_fields.push("pending_ratios".to_string());
+        }
+      },
+      ProposalKind::ChangeFee { fee } => {
+        work.fee = fee;
+        updated_fields.push("fee".to_string());
+      },
+      ProposalKind::ChangeFeeToken { fee_token } => {
+        work.fee_token = fee_token;
+        updated_fields.push("fee_token".to_string());
+      },
+    }
+
+    work.updated_date = env::block_timestamp_ms();
+    self.internal_save_work(&work);
+    if updated_fields.iter().any(|field| field == "ratios" || field == "fee") {
+      self.internal_record_revision(&work, updated_fields.clone(), proposal.proposer.clone(), work.updated_date);
+    }
+
+    EventLog::copyright(EventLogVariant::WorkUpdate(vec![WorkUpdateLog {
+      work_id: work.id.clone(),
+      editor_id: proposal.proposer.clone(),
+      updated_fields,
+    }]))
+    .emit();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::test_utils::accounts;
+  use near_sdk::ONE_NEAR;
+
+  use crate::test_utils::{create_song, set_caller_at};
+  use super::*;
+  use crate::Copyright;
+
+  /// accounts(1) wrote a work with accounts(2), accounts(3) and accounts(4)
+  fn setup() -> (Contract, WorkId) {
+    set_caller_at(accounts(1), ONE_NEAR, 0);
+    let mut contract = Contract::new(accounts(0));
+    let collaborators = Some(vec![accounts(2), accounts(3), accounts(4)]);
+    let ratios = Some(vec![(accounts(1), 4_000), (accounts(2), 2_000), (accounts(3), 2_000), (accounts(4), 2_000)]);
+    let work = create_song(&mut contract, collaborators, None, ratios);
+    for index in 2..5 {
+      set_caller_at(accounts(index), ONE_NEAR, 0);
+      contract.accept_collaboration(work.id.clone());
+    }
+    (contract, work.id)
+  }
+
+  #[test]
+  fn collaborator_is_removed_once_the_quorum_approves() {
+    let (mut contract, work_id) = setup();
+    set_caller_at(accounts(2), ONE_NEAR, 1_000);
+    let kind = ProposalKind::RemoveCollaborator { collaborator: accounts(4) };
+    let proposal_id = contract.propose(work_id.clone(), kind);
+
+    // 2 of 4 members voted: the quorum is 3
+    set_caller_at(accounts(3), ONE_NEAR, 2_000);
+    assert_eq!(contract.vote_on_proposal(proposal_id, true), ProposalStatus::InProgress);
+    set_caller_at(accounts(4), ONE_NEAR, 3_000);
+    assert_eq!(contract.vote_on_proposal(proposal_id, false), ProposalStatus::Approved);
+
+    set_caller_at(accounts(1), 0, 4_000);
+    let work = contract.get_work_by_id(work_id.clone()).unwrap();
+    assert_eq!(work.collaborators, vec![accounts(2), accounts(3)]);
+    // The ratio of accounts(4) is shared in proportion
+    assert_eq!(work.ratios, Some(vec![(accounts(1), 5_000), (accounts(2), 2_500), (accounts(3), 2_500)]));
+    assert_eq!(contract.get_proposals_per_work(work_id, None, None)[0].status, ProposalStatus::Approved);
+  }
+
+  #[test]
+  fn proposal_is_rejected_once_it_can_not_pass_and_expires_otherwise() {
+    let (mut contract, work_id) = setup();
+    set_caller_at(accounts(1), ONE_NEAR, 0);
+    let rejected = contract.propose(work_id.clone(), ProposalKind::DeleteWork);
+    let expiring = contract.propose(work_id.clone(), ProposalKind::ChangeFee { fee: U128(ONE_NEAR) });
+
+    set_caller_at(accounts(2), ONE_NEAR, 0);
+    contract.vote_on_proposal(rejected, false);
+    set_caller_at(accounts(3), ONE_NEAR, 0);
+    assert_eq!(contract.vote_on_proposal(rejected, false), ProposalStatus::Rejected);
+
+    let voting_period = GovernancePolicy::default().voting_period;
+    set_caller_at(accounts(4), 0, voting_period + 1);
+    assert_eq!(contract.get_proposal(expiring).unwrap().status, ProposalStatus::Expired);
+    assert!(contract.get_work_by_id(work_id).is_some());
+  }
+
+  #[test]
+  fn approved_proposal_that_no_longer_applies_fails() {
+    let (mut contract, work_id) = setup();
+    set_caller_at(accounts(2), ONE_NEAR, 0);
+    let proposal_id = contract.propose(work_id.clone(), ProposalKind::RemoveCollaborator { collaborator: accounts(4) });
+    set_caller_at(accounts(4), ONE_NEAR, 0);
+    contract.leave_work(work_id.clone());
+
+    set_caller_at(accounts(3), ONE_NEAR, 0);
+    assert_eq!(contract.vote_on_proposal(proposal_id, true), ProposalStatus::InProgress);
+    set_caller_at(accounts(1), ONE_NEAR, 0);
+    assert_eq!(contract.vote_on_proposal(proposal_id, true), ProposalStatus::Failed);
+
+    assert_eq!(contract.get_proposal(proposal_id).unwrap().status, ProposalStatus::Failed);
+    assert_eq!(contract.get_work_by_id(work_id).unwrap().collaborators, vec![accounts(2), accounts(3)]);
+  }
+
+  #[test]
+  fn weighted_votes_weigh_the_ratio_of_the_voter() {
+    let (mut contract, work_id) = setup();
+    let policy = GovernancePolicy { weighted: true, ..GovernancePolicy::default() };
+    let proposal_id = contract.propose(work_id.clone(), ProposalKind::ChangePolicy { policy });
+    for index in 2..4 {
+      set_caller_at(accounts(index), ONE_NEAR, 0);
+      contract.vote_on_proposal(proposal_id, true);
+    }
+
+    set_caller_at(accounts(1), ONE_NEAR, 0);
+    let proposal_id = contract.propose(work_id.clone(), ProposalKind::ChangeFee { fee: U128(ONE_NEAR) });
+    set_caller_at(accounts(2), ONE_NEAR, 1_000);
+    assert_eq!(contract.vote_on_proposal(proposal_id, true), ProposalStatus::InProgress);
+
+    let voting_period = GovernancePolicy::default().voting_period;
+    let tally = ProposalTally {
+      status: ProposalStatus::InProgress,
+      yes_weight: 6_000,
+      no_weight: 0,
+      total_weight: 10_000,
+      quorum_reached: false,
+      time_remaining: voting_period - 1_000,
+    };
+    assert_eq!(contract.get_proposal_tally(proposal_id), Some(tally));
+
+    // 8 000 of 10 000 voted, 6 000 of them approve
+    set_caller_at(accounts(3), ONE_NEAR, 2_000);
+    assert_eq!(contract.vote_on_proposal(proposal_id, false), ProposalStatus::Approved);
+    assert_eq!(contract.get_work_by_id(work_id).unwrap().fee, U128(ONE_NEAR));
+  }
+
+  #[test]
+  #[should_panic(expected = "Only the members of a work can vote")]
+  fn only_members_vote() {
+    let (mut contract, work_id) = setup();
+    let proposal_id = contract.propose(work_id, ProposalKind::DeleteWork);
+    set_caller_at(accounts(5), ONE_NEAR, 0);
+    contract.vote_on_proposal(proposal_id, true);
+  }
+}
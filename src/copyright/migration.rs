@@ -0,0 +1,348 @@
+//! Migration of the state deployed before works were indexed by id.
+//!
+//! The layout version lives under its own storage key next to the contract state. A state without that key
+//! is the deployed layout, `StateVersion::V1`, where every author owned a single `Vec<(WorkId, Work)>` of
+//! works with their plaintext content on chain.
+//!
+//! `migrate` upgrades the contract struct at once. Works can be too many for one call, so they stay in the
+//! per-author vectors until `migrate_works` moves them a batch at a time. Call it right after `migrate` until
+//! it returns true: works not moved yet can not be read until then.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Timestamp, ONE_NEAR};
+
+use crate::content::ContentRef;
+use crate::event::{EventLog, EventLogVariant, NftMintLog};
+use crate::fungible_token::TokenWhitelist;
+use crate::storage::StorageLedger;
+
+use super::{
+  editable_fields, hash_content, Author, Contract, ContractExt, GovernancePolicy, Report, StorageKey, TitleRecord,
+  Vote, Work, WorkId,
+};
+
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+const WORKS_MIGRATION_KEY: &[u8] = b"WORKS_MIGRATION";
+
+/// Layout versions of the contract state.
+#[derive(
+  BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StateVersion {
+  /// Every author owned a single `Vec<(WorkId, Work)>`.
+  V1,
+  /// Works are indexed by id, with a set of work ids per author.
+  V2,
+}
+
+impl StateVersion {
+  pub const CURRENT: StateVersion = StateVersion::V2;
+
+  /// Version of the state in storage.
+  pub fn read() -> Self {
+    env::storage_read(STATE_VERSION_KEY)
+      .map(|bytes| Self::try_from_slice(&bytes).expect("Cannot deserialize the state version"))
+      .unwrap_or(StateVersion::V1)
+  }
+
+  pub fn write(self) {
+    env::storage_write(STATE_VERSION_KEY, &self.try_to_vec().unwrap());
+  }
+}
+
+/// The per-author vectors of the deployed layout. Works are moved out of them as they are migrated.
+#[derive(BorshDeserialize, BorshSerialize)]
+struct WorksMigration {
+  legacy_works: UnorderedMap<AccountId, Vec<(WorkId, WorkV1)>>,
+}
+
+impl WorksMigration {
+  fn read() -> Option<Self> {
+    env::storage_read(WORKS_MIGRATION_KEY)
+      .map(|bytes| Self::try_from_slice(&bytes).expect("Cannot deserialize the works migration"))
+  }
+
+  fn write(&self) {
+    env::storage_write(WORKS_MIGRATION_KEY, &self.try_to_vec().unwrap());
+  }
+
+  fn finish() {
+    env::storage_remove(WORKS_MIGRATION_KEY);
+  }
+}
+
+/// Whether works are still waiting for `migrate_works`.
+pub(crate) fn works_migration_pending() -> bool {
+  env::storage_has_key(WORKS_MIGRATION_KEY)
+}
+
+/// Layout of `Work` in `StateVersion::V1`, with the plaintext content on chain.
+#[derive(BorshDeserialize, BorshSerialize)]
+struct WorkV1 {
+  id: WorkId,
+  name: String,
+  content: String,
+  author: Author,
+  fee: u128,
+  ratings: Vec<(AccountId, u32)>,
+  average_rating: Option<f64>,
+  published_date: Timestamp,
+  updated_date: Timestamp,
+  collaborators: Vec<AccountId>,
+  reported_infringements: Option<Vec<Report>>,
+  ratios: Option<Vec<(AccountId, u32)>>,
+  authorized_users: Vec<AccountId>,
+  votes: Option<Vec<Vote>>,
+}
+
+impl WorkV1 {
+  fn into_current(self) -> Work {
+    Work {
+      content_hash: hash_content(&self.content).into(),
+      // The plaintext content stays on chain as a `data:` URI
+      content: ContentRef::from_legacy(&self.content),
+      id: self.id,
+      name: self.name,
+      author: self.author,
+      // Fees were in whole NEAR
+      fee: U128(self.fee * ONE_NEAR),
+      fee_token: None,
+      ratings: self.ratings,
+      average_rating: self.average_rating,
+      published_date: self.published_date,
+      updated_date: self.updated_date,
+      // Collaborators added before invitations existed stay collaborators
+      collaborators: self.collaborators,
+      invitations: Vec::new(),
+      reported_infringements: self.reported_infringements,
+      // Ratios were in percents
+      ratios: self
+        .ratios
+        .map(|ratios| ratios.into_iter().map(|(account_id, ratio)| (account_id, ratio * 100)).collect()),
+      pending_ratios: None,
+      authorized_users: self.authorized_users,
+      delivery_agent: None,
+      approved_account_ids: Default::default(),
+      next_approval_id: 0,
+      // Votes were not tied to any decision, so they are dropped
+      policy: GovernancePolicy::default(),
+      pending_transfer: None,
+    }
+  }
+}
+
+/// Layout of `Contract` in `StateVersion::V1`.
+#[derive(BorshDeserialize, BorshSerialize)]
+struct ContractV1 {
+  owner_id: AccountId,
+  authors: UnorderedMap<AccountId, Author>,
+  works: UnorderedMap<AccountId, Vec<(WorkId, WorkV1)>>,
+  number_of_authors: u128,
+  number_of_all_works: u128,
+}
+
+impl ContractV1 {
+  /// The works stay in the per-author vectors until `migrate_works` moves them.
+  fn into_current(self) -> Contract {
+    WorksMigration { legacy_works: self.works }.write();
+
+    Contract {
+      owner_id: self.owner_id,
+      authors: self.authors,
+      works: LookupMap::new(StorageKey::WorksById.try_to_vec().unwrap()),
+      works_per_author: UnorderedMap::new(StorageKey::WorksPerAuthor.try_to_vec().unwrap()),
+      number_of_authors: self.number_of_authors,
+      number_of_all_works: self.number_of_all_works,
+      works_nonce: 0,
+      registrations: LookupMap::new(StorageKey::Registrations.try_to_vec().unwrap()),
+      revisions: LookupMap::new(StorageKey::RevisionsPerWork.try_to_vec().unwrap()),
+      content_keys: LookupMap::new(StorageKey::ContentKeys.try_to_vec().unwrap()),
+      storage_balances: StorageLedger::new(StorageKey::StorageBalances.try_to_vec().unwrap()),
+      accepted_tokens: TokenWhitelist::new(StorageKey::AcceptedTokens.try_to_vec().unwrap()),
+      royalties: LookupMap::new(StorageKey::Royalties.try_to_vec().unwrap()),
+      proposals: LookupMap::new(StorageKey::Proposals.try_to_vec().unwrap()),
+      proposals_per_work: LookupMap::new(StorageKey::ProposalsPerWork.try_to_vec().unwrap()),
+      next_proposal_id: 0,
+      titles: LookupMap::new(StorageKey::TitlesPerWork.try_to_vec().unwrap()),
+    }
+  }
+}
+
+#[near_bindgen]
+impl Contract {
+  /// Upgrade the stored state to the current layout. Only the contract account can call it, right after
+  /// deploying new code. Calling it on an up-to-date state changes nothing. Works of the deployed layout are
+  /// left to `migrate_works`.
+  #[private]
+  #[init(ignore_state)]
+  pub fn migrate() -> Self {
+    let contract = match StateVersion::read() {
+      StateVersion::V1 => env::state_read::<ContractV1>().expect("There is no state to migrate").into_current(),
+      StateVersion::V2 => env::state_read().expect("There is no state to migrate"),
+    };
+    StateVersion::CURRENT.write();
+    contract
+  }
+
+  /// Move up to `limit` works of the deployed layout to the current one. Only the contract account can call
+  /// it. Returns true once every work is in the current layout.
+  #[private]
+  pub fn migrate_works(&mut self, limit: u32) -> bool {
+    let mut migration = if let Some(migration) = WorksMigration::read() { migration } else { return true };
+
+    for _ in 0..limit {
+      let legacy_works = &mut migration.legacy_works;
+      let author_id = if let Some(author_id) = legacy_works.keys_as_vector().get(0) { author_id } else { break };
+      let mut work_list = legacy_works.get(&author_id).unwrap();
+      let (_, work) = work_list.remove(0);
+      if work_list.is_empty() {
+        legacy_works.remove(&author_id);
+      } else {
+        legacy_works.insert(&author_id, &work_list);
+      }
+      self.internal_migrate_work(&work.into_current());
+    }
+
+    let done = migration.legacy_works.is_empty();
+    if done {
+      WorksMigration::finish();
+    } else {
+      migration.write();
+    }
+    done
+  }
+
+  /// Layout version of the stored state.
+  pub fn get_state_version(&self) -> StateVersion {
+    StateVersion::read()
+  }
+}
+
+impl Contract {
+  /// Store a work of the deployed layout with the records it did not have: its registration and first
+  /// revision, and its chain of title.
+  fn internal_migrate_work(&mut self, work: &Work) {
+    self.internal_save_work(work);
+    self.internal_record_revision(work, editable_fields(), work.author.id.clone(), work.published_date);
+    self.internal_record_title(&work.id, TitleRecord::origin(work));
+
+    // Works were minted before mint events were logged
+    EventLog::nft(EventLogVariant::NftMint(vec![NftMintLog {
+      owner_id: work.author.id.clone(),
+      token_ids: vec![work.id.clone()],
+      memo: None,
+    }]))
+    .emit();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+  use near_sdk::testing_env;
+
+  use super::*;
+  use crate::Copyright;
+
+  fn setup() {
+    let mut context = VMContextBuilder::new();
+    context.current_account_id(accounts(0)).predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+  }
+
+  fn author(id: AccountId) -> Author {
+    Author { id, name: "Author".to_string(), age: 30, rated_works: Vec::new() }
+  }
+
+  fn work(id: &str, author: &Author) -> WorkV1 {
+    WorkV1 {
+      id: id.to_string(),
+      name: id.to_string(),
+      content: format!("content of {}", id),
+      author: author.clone(),
+      fee: 2,
+      ratings: Vec::new(),
+      average_rating: None,
+      published_date: 0,
+      updated_date: 0,
+      collaborators: Vec::new(),
+      reported_infringements: None,
+      ratios: None,
+      authorized_users: Vec::new(),
+      votes: None,
+    }
+  }
+
+  #[test]
+  fn migrate_v1_layout() {
+    setup();
+    let alice = author(accounts(1));
+    let bob = author(accounts(2));
+
+    let mut v1 = ContractV1 {
+      owner_id: accounts(0),
+      authors: UnorderedMap::new(b"authors".try_to_vec().unwrap()),
+      works: UnorderedMap::new(b"works".try_to_vec().unwrap()),
+      number_of_authors: 2,
+      number_of_all_works: 3,
+    };
+    v1.authors.insert(&alice.id, &alice);
+    v1.authors.insert(&bob.id, &bob);
+    v1.works.insert(&alice.id, &vec![("A1".to_string(), work("A1", &alice)), ("A2".to_string(), work("A2", &alice))]);
+    v1.works.insert(&bob.id, &vec![("B1".to_string(), work("B1", &bob))]);
+    env::state_write(&v1);
+    assert_eq!(StateVersion::read(), StateVersion::V1);
+
+    let mut contract = Contract::migrate();
+
+    assert_eq!(StateVersion::read(), StateVersion::CURRENT);
+    assert_eq!(contract.owner_id, accounts(0));
+    assert_eq!(contract.authors.len(), 2);
+    assert_eq!(contract.number_of_authors, 2);
+    assert_eq!(contract.number_of_all_works, 3);
+    assert!(contract.works.get(&"A1".to_string()).is_none());
+
+    assert!(!contract.migrate_works(2));
+    assert!(contract.migrate_works(2));
+    assert!(contract.migrate_works(2));
+    let mints =
+      get_logs().iter().filter(|log| log.contains(r#""standard":"nep171""#) && log.contains("nft_mint")).count();
+    assert_eq!(mints, 3);
+
+    assert_eq!(contract.works_per_author.get(&alice.id).unwrap().len(), 2);
+    assert_eq!(contract.works_per_author.get(&bob.id).unwrap().to_vec(), vec!["B1".to_string()]);
+    assert_eq!(contract.works.get(&"A2".to_string()).unwrap().author.id, alice.id);
+    assert_eq!(contract.works.get(&"A2".to_string()).unwrap().fee, U128(2 * ONE_NEAR));
+    assert_eq!(contract.verify_work(hash_content("content of B1").into()), Some(("B1".to_string(), bob.id.clone(), 0)));
+    assert_eq!(contract.get_work_revisions("B1".to_string(), None, None).len(), 1);
+    assert_eq!(contract.get_chain_of_title("B1".to_string(), None, None)[0].owner_id, bob.id);
+    assert_eq!(
+      contract.works.get(&"B1".to_string()).unwrap().content.uri,
+      "data:text/plain;base64,Y29udGVudCBvZiBCMQ=="
+    );
+
+    let old_works: UnorderedMap<AccountId, Vec<(WorkId, WorkV1)>> = UnorderedMap::new(b"works".try_to_vec().unwrap());
+    assert!(old_works.get(&alice.id).is_none());
+  }
+
+  #[test]
+  fn migrate_current_layout_keeps_state() {
+    setup();
+    let alice = author(accounts(1));
+
+    let mut contract = Contract::new(accounts(0));
+    contract.internal_save_work(&work("A1", &alice).into_current());
+    env::state_write(&contract);
+
+    let mut contract = Contract::migrate();
+
+    assert_eq!(StateVersion::read(), StateVersion::CURRENT);
+    assert!(contract.migrate_works(10));
+    assert_eq!(contract.works.get(&"A1".to_string()).unwrap().name, "A1");
+    assert_eq!(contract.works_per_author.get(&alice.id).unwrap().len(), 1);
+  }
+}
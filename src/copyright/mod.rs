@@ -0,0 +1,1062 @@
+use std::collections::HashMap;
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap, UnorderedSet, Vector};
+use near_sdk::{near_bindgen, PanicOnDefault, AccountId, Balance, Timestamp, CryptoHash, env};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::json_types::{Base58CryptoHash, Base64VecU8, U128};
+
+use crate::content::ContentRef;
+use crate::fungible_token::TokenWhitelist;
+use crate::storage::StorageLedger;
+use crate::event::{
+  AccessPurchaseLog, AuthorCreateLog, ContentKeyDeliverLog, EventLog, EventLogVariant, NftBurnLog, NftMintLog,
+  WorkCreateLog, WorkDeleteLog, WorkRateLog, WorkReportLog, WorkUpdateLog,
+};
+
+mod collaboration;
+mod fungible_token;
+mod governance;
+mod migration;
+mod nft;
+mod royalty;
+mod storage;
+mod transfer;
+
+pub use collaboration::Invitation;
+pub use governance::{
+  GovernancePolicy, Proposal, ProposalId, ProposalKind, ProposalStatus, ProposalTally, RatioRedistribution,
+};
+pub use migration::StateVersion;
+pub use nft::{NonFungibleTokenApprovalReceiver, Payout, WorkToken};
+pub use royalty::{RoyaltyBalance, RoyaltyCallbacks};
+pub use transfer::{TitleRecord, TransferOffer};
+
+/// `WorkId` identifies a work. New ids are the base58 sha256 of the author, a contract-wide nonce and the
+/// content hash, so they never depend on the name or the block time.
+pub type WorkId = String;
+
+/// Length of the X25519 public keys buyers pass to `get_access`.
+const PUBLIC_KEY_LEN: usize = 32;
+
+/// Ratios of a work are basis points of what it earns, so they sum to 10 000.
+const FULL_RATIO: u32 = 10_000;
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    owner_id: AccountId,
+    authors: UnorderedMap<AccountId, Author>,
+    works: LookupMap<WorkId, Work>,
+    works_per_author: UnorderedMap<AccountId, UnorderedSet<WorkId>>,
+    number_of_authors: u128,
+    number_of_all_works: u128,
+    works_nonce: u64,
+    registrations: LookupMap<CryptoHash, Registration>,
+    revisions: LookupMap<WorkId, Vector<WorkRevision>>,
+    content_keys: LookupMap<WorkId, UnorderedMap<AccountId, ContentKey>>,
+    storage_balances: StorageLedger,
+    /// NEP-141 token contracts a work fee can be set in.
+    accepted_tokens: TokenWhitelist,
+    /// Royalties credited to an account and not withdrawn yet, one entry per token.
+    royalties: LookupMap<AccountId, Vec<RoyaltyBalance>>,
+    /// Governance proposals by id.
+    proposals: LookupMap<ProposalId, Proposal>,
+    /// Ids of the proposals on a work, oldest first.
+    proposals_per_work: LookupMap<WorkId, Vector<ProposalId>>,
+    next_proposal_id: ProposalId,
+    /// Chain of title of every work, oldest owner first.
+    titles: LookupMap<WorkId, Vector<TitleRecord>>
+}
+
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Author {
+  id: AccountId,
+  name: String,
+  age: u32,
+  rated_works: Vec<(WorkId, u32)>
+}
+
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Work {
+    id: WorkId,
+    name: String,
+    /// Off-chain location of the encrypted content. `content_hash` is the sha256 of the plaintext.
+    content: ContentRef,
+    content_hash: Base58CryptoHash,
+    author: Author,
+    /// Price of access, in yoctoNEAR or in the smallest unit of `fee_token`.
+    fee: U128,
+    /// Token contract the fee is paid in through `ft_transfer_call`, or None for NEAR.
+    fee_token: Option<AccountId>,
+    ratings: Vec<(AccountId, u32)>,
+    average_rating: Option<f64>,
+    published_date: Timestamp,
+    updated_date: Timestamp,
+    collaborators: Vec<AccountId>,
+    /// Accounts invited to collaborate that have not answered yet.
+    invitations: Vec<Invitation>,
+    reported_infringements: Option<Vec<Report>>,
+    /// Share of every member in what the work earns, in basis points. Equal shares when None.
+    ratios: Option<Vec<(AccountId, u32)>>,
+    /// Ratios waiting for invited accounts they name to accept. They replace `ratios` once everyone accepted.
+    pending_ratios: Option<Vec<(AccountId, u32)>>,
+    authorized_users: Vec<AccountId>,
+    /// Account allowed to deliver content keys besides the author.
+    delivery_agent: Option<AccountId>,
+    /// NEP-178 approvals to transfer the work, by approved account.
+    approved_account_ids: HashMap<AccountId, u64>,
+    next_approval_id: u64,
+    /// How the members of the work decide on proposals.
+    policy: GovernancePolicy,
+    /// Offer of the work to a new author, waiting for it to accept.
+    pending_transfer: Option<TransferOffer>
+}
+
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Report {
+  reporter: AccountId,
+  reason: String,
+  timestamp: Timestamp,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Vote {
+  voter: AccountId,
+  decision: bool,
+  timestamp: Timestamp
+}
+
+/// First registration of a content hash. It is never overwritten, so it proves who registered the content first.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Registration {
+  work_id: WorkId,
+  author_id: AccountId,
+  timestamp: Timestamp,
+}
+
+/// One entry of the append-only history of a work. Revision 0 is the work as created; every later revision
+/// holds the editable fields right after an update and names the fields that changed.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkRevision {
+  revision: u32,
+  changed_fields: Vec<String>,
+  name: String,
+  content: ContentRef,
+  content_hash: Base58CryptoHash,
+  fee: U128,
+  ratios: Option<Vec<(AccountId, u32)>>,
+  editor_id: AccountId,
+  timestamp: Timestamp,
+}
+
+/// Content key of a work for one buyer: the X25519 public key given to `get_access` and, once the author
+/// or the delivery agent has posted it, the content key wrapped for that public key.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContentKey {
+  public_key: Base64VecU8,
+  wrapped_key: Option<Base64VecU8>,
+}
+
+/// New content of a work for `update_work`: the reference to the ciphertext and the sha256 of the plaintext.
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContentUpdate {
+  content: ContentRef,
+  content_hash: Base58CryptoHash,
+}
+
+/// Keys of the persistent collections added after the first release.
+/// `authors` keeps its original `b"authors"` prefix.
+#[derive(BorshSerialize)]
+pub enum StorageKey {
+  WorksById,
+  WorksPerAuthor,
+  WorksPerAuthorInner { author_id_hash: CryptoHash },
+  Registrations,
+  RevisionsPerWork,
+  RevisionsPerWorkInner { work_id_hash: CryptoHash },
+  ContentKeys,
+  ContentKeysInner { work_id_hash: CryptoHash },
+  StorageBalances,
+  AcceptedTokens,
+  Royalties,
+  Proposals,
+  ProposalsPerWork,
+  ProposalsPerWorkInner { work_id_hash: CryptoHash },
+  TitlesPerWork,
+  TitlesPerWorkInner { work_id_hash: CryptoHash },
+}
+
+#[near_bindgen]
+impl Contract {
+  /// Initialize the contract and record the current state version.
+  #[init]
+  pub fn new(owner_id: AccountId) -> Self {
+    StateVersion::CURRENT.write();
+
+    Self {
+      owner_id,
+      authors: UnorderedMap::new(b"authors".try_to_vec().unwrap()),
+      works: LookupMap::new(StorageKey::WorksById.try_to_vec().unwrap()),
+      works_per_author: UnorderedMap::new(StorageKey::WorksPerAuthor.try_to_vec().unwrap()),
+      number_of_authors: 0,
+      number_of_all_works: 0,
+      works_nonce: 0,
+      registrations: LookupMap::new(StorageKey::Registrations.try_to_vec().unwrap()),
+      revisions: LookupMap::new(StorageKey::RevisionsPerWork.try_to_vec().unwrap()),
+      content_keys: LookupMap::new(StorageKey::ContentKeys.try_to_vec().unwrap()),
+      storage_balances: StorageLedger::new(StorageKey::StorageBalances.try_to_vec().unwrap()),
+      accepted_tokens: TokenWhitelist::new(StorageKey::AcceptedTokens.try_to_vec().unwrap()),
+      royalties: LookupMap::new(StorageKey::Royalties.try_to_vec().unwrap()),
+      proposals: LookupMap::new(StorageKey::Proposals.try_to_vec().unwrap()),
+      proposals_per_work: LookupMap::new(StorageKey::ProposalsPerWork.try_to_vec().unwrap()),
+      next_proposal_id: 0,
+      titles: LookupMap::new(StorageKey::TitlesPerWork.try_to_vec().unwrap())
+    }
+  }
+}
+
+impl Work {
+  pub fn calculate_avg_ratings(&self) -> Option<f64> {
+    let total_ratings: u32 = self.ratings.iter().map(|(_, r)| *r).sum();
+    let total_count = self.ratings.len() as u32;
+
+    if total_count > 0 {
+        let avg_rating = f64::from(total_ratings) / f64::from(total_count);
+        Some(avg_rating)
+    } else {
+        None
+    }
+  }
+}
+
+impl Contract {
+  /// Store a work under its id and index it under its author.
+  fn internal_save_work(&mut self, work: &Work) {
+    let author_id = &work.author.id;
+    let mut work_ids = self.works_per_author.get(author_id).unwrap_or_else(|| {
+      UnorderedSet::new(
+        StorageKey::WorksPerAuthorInner { author_id_hash: hash_account_id(author_id) }.try_to_vec().unwrap(),
+      )
+    });
+
+    if work_ids.insert(&work.id) {
+      self.works_per_author.insert(author_id, &work_ids);
+    }
+    self.works.insert(&work.id, work);
+  }
+
+  /// Remove a work and drop it from its author's index.
+  /// Returns `false` when the author does not own a work with this id.
+  fn internal_remove_work(&mut self, author_id: &AccountId, work_id: &WorkId) -> bool {
+    let mut work_ids = self.works_per_author.get(author_id).expect("Author does not exist");
+
+    if !work_ids.remove(work_id) {
+      return false;
+    }
+
+    if work_ids.is_empty() {
+      self.works_per_author.remove(author_id);
+    } else {
+      self.works_per_author.insert(author_id, &work_ids);
+    }
+    self.works.remove(work_id);
+    true
+  }
+
+  /// Delete a work and emit `WorkDelete`.
+  fn internal_delete_work(&mut self, work: &Work) -> bool {
+    if !self.internal_remove_work(&work.author.id, &work.id) {
+      return false;
+    }
+    self.number_of_all_works -= 1;
+
+    EventLog::copyright(EventLogVariant::WorkDelete(vec![WorkDeleteLog {
+      work_id: work.id.clone(),
+      author_id: work.author.id.clone(),
+    }]))
+    .emit();
+    EventLog::nft(EventLogVariant::NftBurn(vec![NftBurnLog {
+      owner_id: work.author.id.clone(),
+      token_ids: vec![work.id.clone()],
+      authorized_id: None,
+      memo: None,
+    }]))
+    .emit();
+    true
+  }
+
+  /// Register the current content hash of a work, unless someone registered it before, and append the
+  /// current state of the work to its revision history.
+  fn internal_record_revision(
+    &mut self,
+    work: &Work,
+    changed_fields: Vec<String>,
+    editor_id: AccountId,
+    timestamp: Timestamp,
+  ) {
+    let content_hash: CryptoHash = work.content_hash.into();
+    if !self.registrations.contains_key(&content_hash) {
+      let registration = Registration { work_id: work.id.clone(), author_id: work.author.id.clone(), timestamp };
+      self.registrations.insert(&content_hash, &registration);
+    }
+
+    let mut revisions = self.revisions.get(&work.id).unwrap_or_else(|| {
+      Vector::new(
+        StorageKey::RevisionsPerWorkInner { work_id_hash: env::sha256_array(work.id.as_bytes()) }
+          .try_to_vec()
+          .unwrap(),
+      )
+    });
+    revisions.push(&WorkRevision {
+      revision: revisions.len() as u32,
+      changed_fields,
+      name: work.name.clone(),
+      content: work.content.clone(),
+      content_hash: work.content_hash,
+      fee: work.fee,
+      ratios: work.ratios.clone(),
+      editor_id,
+      timestamp,
+    });
+    self.revisions.insert(&work.id, &revisions);
+  }
+
+  /// Give a buyer access to a work, ask for its content key and credit `amount`, what the contract received
+  /// for it, to the royalty balances of the members of the work in the fee token of the work.
+  fn internal_purchase_access(&mut self, mut work: Work, buyer_id: AccountId, public_key: Base64VecU8, amount: U128) {
+    assert!(!work.authorized_users.contains(&buyer_id), "You already have access to this work");
+    work.authorized_users.push(buyer_id.clone());
+    self.internal_save_work(&work);
+    self.internal_request_content_key(&work.id, &buyer_id, public_key);
+
+    EventLog::copyright(EventLogVariant::AccessPurchase(vec![AccessPurchaseLog {
+      work_id: work.id.clone(),
+      buyer_id,
+      amount,
+      token_id: work.fee_token.clone(),
+    }]))
+    .emit();
+
+    self.internal_distribute_funds(&work, amount.0);
+  }
+
+  /// Record the public key a buyer wants the content key of a work wrapped for. A new key replaces the
+  /// previous one and drops the key delivered for it.
+  fn internal_request_content_key(&mut self, work_id: &WorkId, account_id: &AccountId, public_key: Base64VecU8) {
+    assert_eq!(public_key.0.len(), PUBLIC_KEY_LEN, "Public key must be a 32-byte X25519 key");
+
+    let mut keys = self.content_keys.get(work_id).unwrap_or_else(|| {
+      UnorderedMap::new(
+        StorageKey::ContentKeysInner { work_id_hash: env::sha256_array(work_id.as_bytes()) }.try_to_vec().unwrap(),
+      )
+    });
+    keys.insert(account_id, &ContentKey { public_key, wrapped_key: None });
+    self.content_keys.insert(work_id, &keys);
+  }
+}
+
+pub trait Copyright {
+  fn create_author(&mut self, name: String, age: u32) -> Author;
+  fn create_work(&mut self, 
+    name: String, 
+    content: ContentRef, 
+    collaborators: Option<Vec<AccountId>>,
+    fee: Option<U128>,
+    ratios: Option<Vec<(AccountId, u32)>>,
+    content_hash: Base58CryptoHash
+  ) -> Work;
+  fn update_author(&mut self, name: Option<String>, age: Option<u32>) -> Author;
+  fn update_work(
+      &mut self, 
+      work_id: WorkId, 
+      name: Option<String>, 
+      content: Option<ContentUpdate>, 
+      fee: Option<U128>,
+      average_rating: Option<f64>,
+      ratios: Option<Vec<(AccountId, u32)>>
+  ) -> Work;
+  fn delete_work(&mut self, work_id: WorkId) -> bool;
+  fn delete_author(&mut self, author_id: AccountId) -> bool;
+  fn get_work_by_id(&self, work_id: WorkId) -> Option<Work>;
+  fn get_all_authors(&self) -> Vec<Author>;
+  fn get_all_works_of_author(&self, author_id: AccountId) -> Vec<Work>;
+  fn get_all_works(&self) -> Vec<Work>;
+  fn get_author_by_id(&self, author_id: AccountId) -> Option<Author>;
+  fn rate_work(&mut self, work_id: WorkId, rating: u32) -> bool;
+  fn report_infringement(&mut self, work_id: WorkId, reason: String) -> bool;
+  fn get_access(&mut self, work_id: WorkId, public_key: Base64VecU8) -> bool;
+  fn verify_work(&self, content_hash: Base58CryptoHash) -> Option<(WorkId, AccountId, Timestamp)>;
+  fn get_work_revisions(&self, work_id: WorkId, from_index: Option<u32>, limit: Option<u32>) -> Vec<WorkRevision>;
+  fn get_work_at_revision(&self, work_id: WorkId, revision: u32) -> Option<Work>;
+  fn set_delivery_agent(&mut self, work_id: WorkId, delivery_agent: Option<AccountId>);
+  fn deliver_content_key(&mut self, work_id: WorkId, account_id: AccountId, wrapped_key: Base64VecU8);
+  fn get_content_key(&self, work_id: WorkId, account_id: AccountId) -> Option<ContentKey>;
+  fn get_pending_content_keys(
+    &self,
+    work_id: WorkId,
+    from_index: Option<u32>,
+    limit: Option<u32>
+  ) -> Vec<(AccountId, Base64VecU8)>;
+}
+
+#[near_bindgen]
+impl Copyright for Contract {
+  #[payable]
+  fn create_author(&mut self, name: String, age: u32) -> Author {
+      let initial_storage = env::storage_usage();
+      let author = Author {
+          id: env::predecessor_account_id(),
+          name, age,
+          rated_works: Vec::new(),
+      };
+
+      self.authors.insert(&author.id, &author);
+      self.number_of_authors += 1;
+
+      EventLog::copyright(EventLogVariant::AuthorCreate(vec![AuthorCreateLog {
+        author_id: author.id.clone(),
+        name: author.name.clone(),
+      }]))
+      .emit();
+
+      self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+      author
+  }
+
+  #[payable]
+  fn create_work(
+    &mut self, 
+    name: String, 
+    content: ContentRef, 
+    collaborators: Option<Vec<AccountId>>, 
+    fee: Option<U128>,
+    ratios: Option<Vec<(AccountId, u32)>>,
+    content_hash: Base58CryptoHash
+  ) -> Work {
+      let initial_storage = env::storage_usage();
+      assert!(!migration::works_migration_pending(), "Works are being migrated");
+      let author = self.authors.get(&env::predecessor_account_id()).expect("There is no author");
+      content.assert_valid();
+      let content_hash = CryptoHash::from(content_hash);
+      let id = generate_work_id(&author.id, self.works_nonce, &content_hash);
+      assert!(!self.works.contains_key(&id), "Work id already exists");
+      self.works_nonce += 1;
+      
+      let fee = fee.unwrap_or(U128(0));
+
+      let mut work_obj = Work {
+          id, name, content, fee,
+          fee_token: None,
+          content_hash: content_hash.into(),
+          author: author.clone(),
+          ratings: Vec::new(),
+          average_rating: None,
+          collaborators: Vec::new(),
+          invitations: Vec::new(),
+          published_date: env::block_timestamp_ms(), 
+          updated_date: env::block_timestamp_ms(),
+          reported_infringements: None,
+          ratios: None,
+          pending_ratios: None,
+          authorized_users: Vec::new(),
+          delivery_agent: None,
+          approved_account_ids: HashMap::new(),
+          next_approval_id: 0,
+          policy: GovernancePolicy::default(),
+          pending_transfer: None
+      };
+
+      // Collaborators only join, and ratios naming them only apply, once they accept
+      self.internal_invite(&mut work_obj, collaborators.unwrap_or_default(), &author.id);
+      if let Some(ratios) = ratios {
+        self.internal_set_ratios(&mut work_obj, ratios);
+      }
+      
+      self.number_of_all_works += 1;
+
+      self.authors.insert(&author.id, &author);
+      
+      self.internal_save_work(&work_obj);
+      self.internal_record_revision(
+        &work_obj,
+        editable_fields(),
+        author.id.clone(),
+        work_obj.published_date,
+      );
+      self.internal_record_title(&work_obj.id, TitleRecord::origin(&work_obj));
+
+      EventLog::copyright(EventLogVariant::WorkCreate(vec![WorkCreateLog {
+        work_id: work_obj.id.clone(),
+        author_id: work_obj.author.id.clone(),
+        collaborators: work_obj.collaborators.clone(),
+      }]))
+      .emit();
+      EventLog::nft(EventLogVariant::NftMint(vec![NftMintLog {
+        owner_id: work_obj.author.id.clone(),
+        token_ids: vec![work_obj.id.clone()],
+        memo: None,
+      }]))
+      .emit();
+      
+      self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+      work_obj
+  }
+
+  #[payable]
+  fn update_author(&mut self, name: Option<String>, age: Option<u32>) -> Author {
+    let initial_storage = env::storage_usage();
+    let mut author = self.authors.get(&env::predecessor_account_id())
+                    .expect("There is no author");
+      
+    if let Some(name) = name {
+      author.name = name;
+    }
+
+    if let Some(age) = age {
+      author.age = age;
+    }
+
+    self.authors.insert(&author.id.clone(), &author);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    
+    author
+  }
+
+  #[payable]
+  fn update_work(
+    &mut self, 
+    work_id: WorkId, 
+    name: Option<String>, 
+    content: Option<ContentUpdate>, 
+    fee: Option<U128>,
+    average_rating: Option<f64>,
+    ratios: Option<Vec<(AccountId, u32)>>,
+  ) -> Work {
+    let initial_storage = env::storage_usage();
+    let mut work = self.works.get(&work_id).expect("There is no work");
+    assert_eq!(work.author.id.clone(), env::predecessor_account_id());
+    let mut updated_fields = Vec::new();
+
+    if let Some(new_name) = name {
+      work.name = new_name;
+      updated_fields.push("name".to_string());
+    }
+
+    if let Some(new_content) = content {
+      new_content.content.assert_valid();
+      work.content = new_content.content;
+      work.content_hash = new_content.content_hash;
+      updated_fields.push("content".to_string());
+    }
+
+    if let Some(new_avg_rating) = average_rating {
+      work.average_rating = Some(new_avg_rating);
+      updated_fields.push("average_rating".to_string());
+    }
+
+    if !work.collaborators.is_empty() {
+      assert!(fee.is_none() && ratios.is_none(), "Changing the fee or the ratios of a shared work needs a proposal");
+    }
+
+    if let Some(new_ratios) = ratios {
+      if self.internal_set_ratios(&mut work, new_ratios) {
+        updated_fields.push("ratios".to_string());
+      }
+    }
+
+    if let Some(fee) = fee {
+      work.fee = fee;
+      updated_fields.push("fee".to_string());
+    }
+
+    work.updated_date = env::block_timestamp_ms();
+
+    self.internal_save_work(&work);
+    self.internal_record_revision(&work, updated_fields.clone(), env::predecessor_account_id(), work.updated_date);
+
+    EventLog::copyright(EventLogVariant::WorkUpdate(vec![WorkUpdateLog {
+      work_id: work.id.clone(),
+      editor_id: env::predecessor_account_id(),
+      updated_fields,
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    work
+  }
+
+  fn delete_work(&mut self, work_id: WorkId) -> bool {
+    let initial_storage = env::storage_usage();
+    let author_id = env::predecessor_account_id();
+
+    if let Some(work) = self.works.get(&work_id) {
+        assert_eq!(work.author.id, author_id, "Unauthorized");
+        assert!(work.collaborators.is_empty(), "Deleting a work with collaborators needs a proposal");
+        let deleted = self.internal_delete_work(&work);
+        self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+        return deleted;
+    }
+
+    false
+  }
+  
+  fn delete_author(&mut self, author_id: AccountId) -> bool {
+    let initial_storage = env::storage_usage();
+    if let Some(author) = self.authors.get(&author_id) {
+        assert_eq!(author.id, env::predecessor_account_id(), "Unauthorized");
+        self.authors.remove(&author_id);
+        self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+
+        return true;
+    }
+
+    false
+  }
+
+  fn get_work_by_id(&self, work_id: WorkId) -> Option<Work> {
+    self.works.get(&work_id)
+  }
+
+
+  fn get_all_authors(&self) -> Vec<Author> {
+    self.authors.values().collect()
+  }
+
+  fn get_all_works_of_author(&self, author_id: AccountId) -> Vec<Work> {
+    let work_ids = self.works_per_author.get(&author_id).map(|ids| ids.to_vec()).unwrap_or_default();
+
+    work_ids
+        .iter()
+        .filter_map(|work_id| self.works.get(work_id))
+        .collect()
+  }
+
+
+  fn get_all_works(&self) -> Vec<Work> {
+    self.works_per_author
+        .values()
+        .flat_map(|ids| ids.to_vec())
+        .filter_map(|work_id| self.works.get(&work_id))
+        .collect()
+  }
+
+  fn get_author_by_id(&self, author_id: AccountId) -> Option<Author> {
+    self.authors.get(&author_id)
+  }
+
+  #[payable]
+  fn rate_work(&mut self, work_id: WorkId, rating: u32) -> bool {
+    let initial_storage = env::storage_usage();
+    let author_id = env::predecessor_account_id();
+
+    if let Some(mut work) = self.works.get(&work_id) {
+      if rating <= 5 {
+        if let Some(index) = work.ratings.iter().position(|(user, _)| *user == author_id) {
+          if rating > 0 {
+            work.ratings[index] = (author_id.clone(), rating);
+          } else {
+            work.ratings.remove(index);
+          }
+        } else {
+          if rating > 0 {
+            work.ratings.push((author_id.clone(), rating));
+          }
+        }
+
+        work.average_rating = work.calculate_avg_ratings();
+        let mut author = self.get_author_by_id(author_id.clone()).expect("There's no author");
+        author.rated_works.push((work.id.clone(), rating));
+        self.authors.insert(&author_id, &author);
+
+        self.internal_save_work(&work);
+
+        EventLog::copyright(EventLogVariant::WorkRate(vec![WorkRateLog {
+          work_id,
+          rater_id: author_id,
+          rating,
+        }]))
+        .emit();
+
+        self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+        return true;
+      }
+    }
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    false
+  }
+
+  #[payable]
+  fn report_infringement(&mut self, work_id: WorkId, reason: String) -> bool {
+    let initial_storage = env::storage_usage();
+    let caller_id = env::predecessor_account_id();
+
+    if let Some(mut work) = self.works.get(&work_id) {
+        if work.reported_infringements.is_none() {
+            work.reported_infringements = Some(Vec::new());
+        }
+
+        if let Some(reported_infringements) = work.reported_infringements.as_mut() {
+            if let Some(report) = reported_infringements.iter_mut().find(|report| report.reporter == caller_id) {
+                report.reason = reason.clone();
+                report.timestamp = env::block_timestamp();
+            } else {
+                let new_report = Report {
+                    reporter: caller_id.clone(),
+                    reason: reason.clone(),
+                    timestamp: env::block_timestamp(),
+                };
+                reported_infringements.push(new_report);
+            }
+            self.internal_save_work(&work);
+
+            EventLog::copyright(EventLogVariant::WorkReport(vec![WorkReportLog {
+              work_id,
+              reporter_id: caller_id,
+              reason,
+            }]))
+            .emit();
+
+            self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+            return true;
+        }
+    }
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+    false
+  }
+
+  #[payable]
+  fn get_access(&mut self, work_id: WorkId, public_key: Base64VecU8) -> bool {
+    let initial_storage = env::storage_usage();
+    let work = self.works.get(&work_id).expect("There is no work");
+    let fee = work.fee.0;
+    let attached_deposit = env::attached_deposit();
+
+    if !is_member(&work, &env::predecessor_account_id()) {
+      assert!(work.fee_token.is_none(), "The fee of this work is paid with ft_transfer_call");
+      assert!(attached_deposit >= fee, "Attach at least {} yoctoNEAR to get access", fee);
+      self.internal_purchase_access(work, env::predecessor_account_id(), public_key, U128(fee));
+
+      // What is left of the deposit after the fee pays for storage, then it is refunded
+      self.storage_balances.charge_caller(initial_storage, attached_deposit - fee);
+      return true;
+    }
+
+    // Members have access already. Their deposit goes back
+    self.storage_balances.charge_caller(initial_storage, attached_deposit);
+    false
+  }
+
+  fn verify_work(&self, content_hash: Base58CryptoHash) -> Option<(WorkId, AccountId, Timestamp)> {
+    self.registrations
+        .get(&content_hash.into())
+        .map(|registration| (registration.work_id, registration.author_id, registration.timestamp))
+  }
+
+  fn get_work_revisions(&self, work_id: WorkId, from_index: Option<u32>, limit: Option<u32>) -> Vec<WorkRevision> {
+    if !self.works.contains_key(&work_id) {
+      return vec![];
+    }
+    let revisions = if let Some(revisions) = self.revisions.get(&work_id) { revisions } else { return vec![] };
+
+    revisions.iter().skip(from_index.unwrap_or(0) as usize).take(limit.unwrap_or(20) as usize).collect()
+  }
+
+  fn get_work_at_revision(&self, work_id: WorkId, revision: u32) -> Option<Work> {
+    let mut work = self.works.get(&work_id)?;
+    let revision = self.revisions.get(&work_id)?.get(revision as u64)?;
+
+    work.name = revision.name;
+    work.content = revision.content;
+    work.content_hash = revision.content_hash;
+    work.fee = revision.fee;
+    work.ratios = revision.ratios;
+    work.updated_date = revision.timestamp;
+
+    Some(work)
+  }
+
+  #[payable]
+  fn set_delivery_agent(&mut self, work_id: WorkId, delivery_agent: Option<AccountId>) {
+    let initial_storage = env::storage_usage();
+    let mut work = self.works.get(&work_id).expect("There is no work");
+    assert_eq!(work.author.id, env::predecessor_account_id(), "Unauthorized");
+
+    work.delivery_agent = delivery_agent;
+    self.internal_save_work(&work);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  #[payable]
+  fn deliver_content_key(&mut self, work_id: WorkId, account_id: AccountId, wrapped_key: Base64VecU8) {
+    let initial_storage = env::storage_usage();
+    let work = self.works.get(&work_id).expect("There is no work");
+    let caller_id = env::predecessor_account_id();
+    assert!(
+      caller_id == work.author.id || work.delivery_agent.as_ref() == Some(&caller_id),
+      "Only the author or the delivery agent can deliver content keys"
+    );
+
+    let mut keys = self.content_keys.get(&work_id).expect("There is no content key request");
+    let mut content_key = keys.get(&account_id).expect("There is no content key request");
+    content_key.wrapped_key = Some(wrapped_key);
+    keys.insert(&account_id, &content_key);
+
+    EventLog::copyright(EventLogVariant::ContentKeyDeliver(vec![ContentKeyDeliverLog {
+      work_id,
+      account_id,
+      delivered_by: caller_id,
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  fn get_content_key(&self, work_id: WorkId, account_id: AccountId) -> Option<ContentKey> {
+    self.content_keys.get(&work_id).and_then(|keys| keys.get(&account_id))
+  }
+
+  fn get_pending_content_keys(
+    &self,
+    work_id: WorkId,
+    from_index: Option<u32>,
+    limit: Option<u32>
+  ) -> Vec<(AccountId, Base64VecU8)> {
+    let keys = if let Some(keys) = self.content_keys.get(&work_id) { keys } else { return vec![] };
+
+    keys.iter()
+        .filter(|(_, content_key)| content_key.wrapped_key.is_none())
+        .skip(from_index.unwrap_or(0) as usize)
+        .take(limit.unwrap_or(20) as usize)
+        .map(|(account_id, content_key)| (account_id, content_key.public_key))
+        .collect()
+  }
+}
+
+/// Fields that `update_work` can change. The first revision of a work lists all of them.
+fn editable_fields() -> Vec<String> {
+  ["name", "content", "fee", "ratios"].iter().map(|field| field.to_string()).collect()
+}
+
+/// Whether the account is the author or a collaborator of the work.
+fn is_member(work: &Work, account_id: &AccountId) -> bool {
+  &work.author.id == account_id || work.collaborators.contains(account_id)
+}
+
+fn assert_one_yocto() {
+  assert_eq!(env::attached_deposit(), 1, "Requires attached deposit of exactly 1 yoctoNEAR");
+}
+
+/// Whether the ratios sum to `FULL_RATIO`.
+fn check_valid_ratios(ratios: &[(AccountId, u32)]) -> Result<(), String> {
+  let total_ratio: u32 = ratios.iter().map(|(_, ratio)| *ratio).sum();
+  if total_ratio != FULL_RATIO {
+    return Err(format!("Ratios must sum to {} basis points", FULL_RATIO));
+  }
+  Ok(())
+}
+
+/// Split `total_amount` between the members of a work by its ratios, or in equal parts when it has none.
+/// The rounding remainder goes to the author.
+fn split_by_ratios(work: &Work, total_amount: Balance) -> Vec<(AccountId, Balance)> {
+  let mut shares: Vec<(AccountId, Balance)> = match &work.ratios {
+    Some(ratios) => ratios
+      .iter()
+      .map(|(account_id, ratio)| (account_id.clone(), total_amount * Balance::from(*ratio) / Balance::from(FULL_RATIO)))
+      .collect(),
+    None => {
+      let equal_share = total_amount / (work.collaborators.len() as Balance + 1);
+      std::iter::once(&work.author.id)
+        .chain(work.collaborators.iter())
+        .map(|account_id| (account_id.clone(), equal_share))
+        .collect()
+    },
+  };
+
+  let remainder = total_amount - shares.iter().map(|(_, amount)| amount).sum::<Balance>();
+  match shares.iter_mut().find(|(account_id, _)| account_id == &work.author.id) {
+    Some((_, amount)) => *amount += remainder,
+    None => shares.push((work.author.id.clone(), remainder)),
+  }
+  shares
+}
+
+/// Id of a new work: base58 of sha256(author_id | nonce | content hash).
+fn generate_work_id(author_id: &AccountId, nonce: u64, content_hash: &CryptoHash) -> WorkId {
+  let mut preimage = Vec::with_capacity(author_id.as_str().len() + 1 + 8 + content_hash.len());
+  preimage.extend_from_slice(author_id.as_bytes());
+  // account ids never contain a zero byte, so the author part can not run into the nonce
+  preimage.push(0);
+  preimage.extend_from_slice(&nonce.to_le_bytes());
+  preimage.extend_from_slice(content_hash);
+
+  near_sdk::bs58::encode(env::sha256(&preimage)).into_string()
+}
+
+/// SHA-256 of the content of a work.
+fn hash_content(content: &str) -> CryptoHash {
+  env::sha256_array(content.as_bytes())
+}
+
+//used to generate a unique prefix in our storage collections (this is to avoid data collisions)
+fn hash_account_id(account_id: &AccountId) -> CryptoHash {
+  let mut hash = CryptoHash::default();
+  hash.copy_from_slice(&env::sha256(account_id.as_bytes()));
+  hash
+}
+
+#[cfg(test)]
+mod tests {
+  use content_crypto::{decrypt_content, encrypt_content, unwrap_key, wrap_key, ContentKey as SecretKey, KeyPair};
+  use near_sdk::test_utils::accounts;
+  use near_sdk::ONE_NEAR;
+
+  use crate::test_utils::{content_ref, create_song, set_caller, transferred_deposits};
+  use super::*;
+  use crate::storage::StorageManagement;
+
+  #[test]
+  fn buyer_decrypts_content_with_delivered_key() {
+    set_caller(accounts(1), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    contract.create_author("Alice".to_string(), 30);
+
+    let secret_key = SecretKey::generate();
+    let encrypted = encrypt_content(&secret_key, b"lyrics");
+    let content_hash = content_crypto::hash_content(b"lyrics");
+    let content = content_ref("ipfs://bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku", &encrypted.ciphertext);
+    let work = contract.create_work("Song".to_string(), content, None, None, None, content_hash.into());
+    assert_eq!(contract.verify_work(content_hash.into()).unwrap().1, accounts(1));
+
+    set_caller(accounts(2), ONE_NEAR);
+    contract.storage_deposit(None, None);
+    set_caller(accounts(2), 0);
+    let buyer = KeyPair::generate();
+    assert!(contract.get_access(work.id.clone(), buyer.public.to_vec().into()));
+    assert_eq!(contract.get_pending_content_keys(work.id.clone(), None, None).len(), 1);
+
+    set_caller(accounts(1), ONE_NEAR);
+    contract.deliver_content_key(work.id.clone(), accounts(2), wrap_key(&secret_key, &buyer.public).into());
+    assert!(contract.get_pending_content_keys(work.id.clone(), None, None).is_empty());
+
+    set_caller(accounts(2), 0);
+    let wrapped_key = contract.get_content_key(work.id.clone(), accounts(2)).unwrap().wrapped_key.unwrap();
+    let unwrapped = unwrap_key(&wrapped_key.0, &buyer.secret).unwrap();
+    let content = contract.get_work_by_id(work.id).unwrap().content;
+    assert_eq!(CryptoHash::from(content.hash), content_crypto::hash_content(&encrypted.ciphertext));
+    assert_eq!(decrypt_content(&unwrapped, &encrypted.ciphertext).unwrap(), b"lyrics");
+  }
+
+  #[test]
+  #[should_panic(expected = "Only the author or the delivery agent can deliver content keys")]
+  fn stranger_cannot_deliver_key() {
+    set_caller(accounts(1), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    let work = create_song(&mut contract, None, None, None);
+
+    set_caller(accounts(3), 0);
+    contract.deliver_content_key(work.id, accounts(2), vec![0; 80].into());
+  }
+
+  #[test]
+  #[should_panic(expected = "Content uri must start with ipfs://, ar://, https:// or http://")]
+  fn content_must_be_off_chain() {
+    set_caller(accounts(1), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    contract.create_author("Alice".to_string(), 30);
+
+    let content = content_ref("data:text/plain;base64,c29uZw==", b"song");
+    contract.create_work("Song".to_string(), content, None, None, None, [0; 32].into());
+  }
+
+  #[test]
+  fn access_fee_is_exact_and_over_payment_is_refunded() {
+    set_caller(accounts(1), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    let fee = 15 * ONE_NEAR / 10;
+    let work = create_song(&mut contract, None, Some(U128(fee)), None);
+
+    set_caller(accounts(2), 2 * ONE_NEAR);
+    assert!(contract.get_access(work.id, vec![1; 32].into()));
+
+    let transfers = transferred_deposits();
+    // The rest of the deposit paid for storage and came back
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].0, accounts(2));
+    assert!(transfers[0].1 > 0 && transfers[0].1 < 2 * ONE_NEAR - fee);
+    assert_eq!(contract.get_royalties(accounts(1)), vec![RoyaltyBalance { token_id: None, amount: U128(fee) }]);
+  }
+
+  #[test]
+  #[should_panic(expected = "You already have access to this work")]
+  fn access_is_bought_once() {
+    set_caller(accounts(1), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    let work = create_song(&mut contract, None, Some(U128(ONE_NEAR)), None);
+
+    set_caller(accounts(2), 2 * ONE_NEAR);
+    assert!(contract.get_access(work.id.clone(), vec![1; 32].into()));
+    contract.get_access(work.id, vec![1; 32].into());
+  }
+
+  #[test]
+  fn member_gets_the_deposit_back() {
+    set_caller(accounts(1), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    let work = create_song(&mut contract, None, Some(U128(ONE_NEAR)), None);
+
+    set_caller(accounts(1), 2 * ONE_NEAR);
+    assert!(!contract.get_access(work.id, vec![1; 32].into()));
+
+    assert_eq!(transferred_deposits(), vec![(accounts(1), 2 * ONE_NEAR)]);
+  }
+
+  #[test]
+  fn storage_is_paid_from_the_storage_balance() {
+    set_caller(accounts(1), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    let deposited = contract.storage_deposit(None, None);
+    assert_eq!(deposited.available.0, ONE_NEAR - StorageLedger::min_balance());
+
+    set_caller(accounts(1), 0);
+    let initial_storage = env::storage_usage();
+    contract.create_author("Alice".to_string(), 30);
+    let cost = Balance::from(env::storage_usage() - initial_storage) * env::storage_byte_cost();
+
+    let balance = contract.storage_balance_of(accounts(1)).unwrap();
+    assert!(cost > 0);
+    assert_eq!(balance.total.0, ONE_NEAR - cost);
+  }
+
+  #[test]
+  fn freed_storage_is_credited_back() {
+    set_caller(accounts(1), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    contract.storage_deposit(None, None);
+
+    set_caller(accounts(1), 0);
+    contract.create_author("Alice".to_string(), 30);
+    assert!(contract.storage_balance_of(accounts(1)).unwrap().total.0 < ONE_NEAR);
+
+    assert!(contract.delete_author(accounts(1)));
+    assert_eq!(contract.storage_balance_of(accounts(1)).unwrap().total, U128(ONE_NEAR));
+  }
+
+  #[test]
+  #[should_panic(expected = "call storage_deposit to pay for storage")]
+  fn unpaid_storage_is_rejected() {
+    set_caller(accounts(1), 0);
+    let mut contract = Contract::new(accounts(0));
+    contract.create_author("Alice".to_string(), 30);
+  }
+}
@@ -0,0 +1,286 @@
+//! Works as NEP-171 tokens, so marketplaces can sell them.
+//!
+//! The token id is the work id and the owner is the author. The author approves a marketplace (NEP-178),
+//! which sells the work with `nft_transfer_payout` and pays the price as `nft_payout` says (NEP-199): split
+//! between the author and the collaborators by the ratios of the work. The buyer must be a registered author
+//! and becomes the author of the work, with the ratio of the previous author. Only a work without
+//! collaborators is approved and transferred this way: a shared work changes author by a `TransferOwnership`
+//! proposal.
+
+use std::collections::HashMap;
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, Gas};
+
+use super::{assert_one_yocto, is_member, split_by_ratios, Contract, ContractExt, Work, WorkId};
+
+const GAS_FOR_NFT_ON_APPROVE: Gas = Gas(10_000_000_000_000);
+
+/// NEP-199 payout: what every account gets out of a sale.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Payout {
+  pub payout: HashMap<AccountId, U128>,
+}
+
+/// NEP-171 view of a work.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkToken {
+  pub token_id: WorkId,
+  pub owner_id: AccountId,
+  pub approved_account_ids: HashMap<AccountId, u64>,
+}
+
+#[ext_contract(ext_approval_receiver)]
+pub trait NonFungibleTokenApprovalReceiver {
+  fn nft_on_approve(&mut self, token_id: WorkId, owner_id: AccountId, approval_id: u64, msg: String);
+}
+
+#[near_bindgen]
+impl Contract {
+  /// Transfer a work to another author. The author or an approved account can call this function. Requires
+  /// exactly 1 yoctoNEAR.
+  #[payable]
+  pub fn nft_transfer(
+    &mut self,
+    receiver_id: AccountId,
+    token_id: WorkId,
+    approval_id: Option<u64>,
+    memo: Option<String>,
+  ) {
+    assert_one_yocto();
+    self.internal_transfer_work(&receiver_id, &token_id, approval_id, None, memo);
+  }
+
+  /// Transfer a work and return how the `balance` the buyer paid must be split (NEP-199). Requires exactly
+  /// 1 yoctoNEAR.
+  #[payable]
+  pub fn nft_transfer_payout(
+    &mut self,
+    receiver_id: AccountId,
+    token_id: WorkId,
+    approval_id: Option<u64>,
+    memo: Option<String>,
+    balance: U128,
+    max_len_payout: Option<u32>,
+  ) -> Payout {
+    assert_one_yocto();
+    let work = self.internal_transfer_work(&receiver_id, &token_id, approval_id, Some(balance), memo);
+    payout(&work, balance, max_len_payout)
+  }
+
+  /// How a sale of a work for `balance` is split between its members (NEP-199).
+  pub fn nft_payout(&self, token_id: WorkId, balance: U128, max_len_payout: Option<u32>) -> Payout {
+    let work = self.works.get(&token_id).expect("There is no work");
+    payout(&work, balance, max_len_payout)
+  }
+
+  pub fn nft_token(&self, token_id: WorkId) -> Option<WorkToken> {
+    self.works.get(&token_id).map(|work| WorkToken {
+      token_id: work.id,
+      owner_id: work.author.id,
+      approved_account_ids: work.approved_account_ids,
+    })
+  }
+
+  /// Let `account_id` transfer a work, and call `nft_on_approve` on it when `msg` is given. Only the author of a
+  /// work without collaborators can call this function.
+  #[payable]
+  pub fn nft_approve(&mut self, token_id: WorkId, account_id: AccountId, msg: Option<String>) {
+    let initial_storage = env::storage_usage();
+    let mut work = self.works.get(&token_id).expect("There is no work");
+    assert_eq!(env::predecessor_account_id(), work.author.id, "Only the author can approve transfers");
+    assert!(work.collaborators.is_empty(), "A shared work is transferred by a TransferOwnership proposal");
+
+    let approval_id = work.next_approval_id;
+    work.approved_account_ids.insert(account_id.clone(), approval_id);
+    work.next_approval_id += 1;
+    self.works.insert(&token_id, &work);
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+
+    if let Some(msg) = msg {
+      ext_approval_receiver::ext(account_id).with_static_gas(GAS_FOR_NFT_ON_APPROVE).nft_on_approve(
+        token_id,
+        work.author.id,
+        approval_id,
+        msg,
+      );
+    }
+  }
+
+  /// Take back the approval of an account. Requires exactly 1 yoctoNEAR.
+  #[payable]
+  pub fn nft_revoke(&mut self, token_id: WorkId, account_id: AccountId) {
+    assert_one_yocto();
+    let mut work = self.works.get(&token_id).expect("There is no work");
+    assert_eq!(env::predecessor_account_id(), work.author.id, "Only the author can revoke approvals");
+
+    if work.approved_account_ids.remove(&account_id).is_some() {
+      self.works.insert(&token_id, &work);
+    }
+  }
+
+  /// Take back every approval of a work. Requires exactly 1 yoctoNEAR.
+  #[payable]
+  pub fn nft_revoke_all(&mut self, token_id: WorkId) {
+    assert_one_yocto();
+    let mut work = self.works.get(&token_id).expect("There is no work");
+    assert_eq!(env::predecessor_account_id(), work.author.id, "Only the author can revoke approvals");
+
+    if !work.approved_account_ids.is_empty() {
+      work.approved_account_ids.clear();
+      self.works.insert(&token_id, &work);
+    }
+  }
+
+  pub fn nft_is_approved(&self, token_id: WorkId, approved_account_id: AccountId, approval_id: Option<u64>) -> bool {
+    let work = self.works.get(&token_id).expect("There is no work");
+    match work.approved_account_ids.get(&approved_account_id) {
+      Some(id) => approval_id.map(|approval_id| approval_id == *id).unwrap_or(true),
+      None => false,
+    }
+  }
+}
+
+impl Contract {
+  /// Make `receiver_id` the author of a work on behalf of its author or an approved account, for the `price`
+  /// the marketplace reports. Returns the work before the transfer.
+  fn internal_transfer_work(
+    &mut self,
+    receiver_id: &AccountId,
+    work_id: &WorkId,
+    approval_id: Option<u64>,
+    price: Option<U128>,
+    memo: Option<String>,
+  ) -> Work {
+    let work = self.works.get(work_id).expect("There is no work");
+    assert!(work.collaborators.is_empty(), "A shared work is transferred by a TransferOwnership proposal");
+    let sender_id = env::predecessor_account_id();
+
+    let authorized_id = if sender_id == work.author.id {
+      None
+    } else {
+      let id = work.approved_account_ids.get(&sender_id).expect("Sender is not approved to transfer this work");
+      if let Some(approval_id) = approval_id {
+        assert_eq!(*id, approval_id, "The approval id does not match");
+      }
+      Some(sender_id)
+    };
+
+    assert!(!is_member(&work, receiver_id), "The receiver is already a member of this work");
+    self.internal_assign_work(&work, receiver_id, authorized_id, price, memo);
+    work
+  }
+}
+
+/// Split `balance` between the members of a work, at most `max_len_payout` of them.
+fn payout(work: &Work, balance: U128, max_len_payout: Option<u32>) -> Payout {
+  let mut payout = HashMap::new();
+  for (account_id, amount) in split_by_ratios(work, balance.0) {
+    let total = payout.entry(account_id).or_insert(U128(0));
+    *total = U128(total.0 + amount);
+  }
+
+  if let Some(max_len_payout) = max_len_payout {
+    assert!(payout.len() <= max_len_payout as usize, "The payout has more than {} receivers", max_len_payout);
+  }
+  Payout { payout }
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::test_utils::accounts;
+  use near_sdk::ONE_NEAR;
+
+  use crate::test_utils::{create_song, set_caller};
+  use super::*;
+  use crate::Copyright;
+
+  /// accounts(1) wrote a work alone and approved the marketplace accounts(4). accounts(3) is an author
+  fn setup() -> (Contract, WorkId) {
+    set_caller(accounts(3), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    contract.create_author("Carol".to_string(), 40);
+
+    let work = create_song(&mut contract, None, None, None);
+    contract.nft_approve(work.id.clone(), accounts(4), None);
+    (contract, work.id)
+  }
+
+  #[test]
+  fn marketplace_sells_a_work() {
+    let (mut contract, work_id) = setup();
+    assert!(contract.nft_is_approved(work_id.clone(), accounts(4), Some(0)));
+
+    set_caller(accounts(4), 1);
+    let payout =
+      contract.nft_transfer_payout(accounts(3), work_id.clone(), Some(0), None, U128(1_000_001), Some(10)).payout;
+    assert_eq!(payout.len(), 1);
+    assert_eq!(payout[&accounts(1)], U128(1_000_001));
+
+    let token = contract.nft_token(work_id.clone()).unwrap();
+    assert_eq!(token.owner_id, accounts(3));
+    assert!(token.approved_account_ids.is_empty());
+    assert_eq!(contract.get_all_works_of_author(accounts(3)).len(), 1);
+    assert!(contract.get_all_works_of_author(accounts(1)).is_empty());
+    assert_eq!(contract.nft_payout(work_id, U128(100), None).payout[&accounts(3)], U128(100));
+  }
+
+  /// accounts(1) wrote a work with accounts(2), after approving the marketplace accounts(4) while the
+  /// invitation was open.
+  fn setup_shared() -> (Contract, WorkId) {
+    set_caller(accounts(3), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    contract.create_author("Carol".to_string(), 40);
+
+    let ratios = Some(vec![(accounts(1), 7_550), (accounts(2), 2_450)]);
+    let work = create_song(&mut contract, Some(vec![accounts(2)]), None, ratios);
+    contract.nft_approve(work.id.clone(), accounts(4), None);
+    set_caller(accounts(2), ONE_NEAR);
+    contract.accept_collaboration(work.id.clone());
+    (contract, work.id)
+  }
+
+  #[test]
+  fn payout_splits_a_shared_work_by_basis_points() {
+    let (contract, work_id) = setup_shared();
+    let payout = contract.nft_payout(work_id, U128(1_000_001), Some(10)).payout;
+    assert_eq!(payout.len(), 2);
+    assert_eq!(payout[&accounts(1)], U128(755_001));
+    assert_eq!(payout[&accounts(2)], U128(245_000));
+  }
+
+  #[test]
+  #[should_panic(expected = "A shared work is transferred by a TransferOwnership proposal")]
+  fn author_does_not_transfer_a_shared_work() {
+    let (mut contract, work_id) = setup_shared();
+    set_caller(accounts(1), 1);
+    contract.nft_transfer(accounts(3), work_id, None, None);
+  }
+
+  #[test]
+  #[should_panic(expected = "A shared work is transferred by a TransferOwnership proposal")]
+  fn marketplace_does_not_sell_a_shared_work() {
+    let (mut contract, work_id) = setup_shared();
+    set_caller(accounts(4), 1);
+    contract.nft_transfer_payout(accounts(3), work_id, Some(0), None, U128(ONE_NEAR), None);
+  }
+
+  #[test]
+  #[should_panic(expected = "A shared work is transferred by a TransferOwnership proposal")]
+  fn shared_work_is_not_approved() {
+    let (mut contract, work_id) = setup_shared();
+    set_caller(accounts(1), ONE_NEAR);
+    contract.nft_approve(work_id, accounts(5), None);
+  }
+
+  #[test]
+  #[should_panic(expected = "Sender is not approved to transfer this work")]
+  fn only_approved_accounts_transfer() {
+    let (mut contract, work_id) = setup();
+    set_caller(accounts(5), 1);
+    contract.nft_transfer(accounts(3), work_id, None, None);
+  }
+}
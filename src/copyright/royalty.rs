@@ -0,0 +1,158 @@
+//! Royalties of works.
+//!
+//! What a work earns is credited to the royalty balances of its members instead of being sent right away.
+//! Each member withdraws its balance with `withdraw_royalties`, and the balance is credited back if the
+//! transfer fails.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, Balance, Gas, PromiseResult};
+
+use crate::event::{EventLog, EventLogVariant, FundsDistributeLog, RoyaltyWithdrawLog};
+
+use super::{assert_one_yocto, split_by_ratios, Contract, ContractExt, Work};
+
+const GAS_FOR_RESOLVE_WITHDRAW: Gas = Gas(5_000_000_000_000);
+
+/// Royalties of an account in one token, or in NEAR when `token_id` is None.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoyaltyBalance {
+  pub token_id: Option<AccountId>,
+  pub amount: U128,
+}
+
+#[ext_contract(ext_self)]
+pub trait RoyaltyCallbacks {
+  /// Callback of `withdraw_royalties`. Credit the amount back if the transfer failed.
+  fn resolve_royalty_withdraw(&mut self, account_id: AccountId, token_id: Option<AccountId>, amount: U128);
+}
+
+#[near_bindgen]
+impl RoyaltyCallbacks for Contract {
+  #[private]
+  fn resolve_royalty_withdraw(&mut self, account_id: AccountId, token_id: Option<AccountId>, amount: U128) {
+    if let PromiseResult::Successful(_) = env::promise_result(0) {
+      EventLog::copyright(EventLogVariant::RoyaltyWithdraw(vec![RoyaltyWithdrawLog { account_id, amount, token_id }]))
+        .emit();
+      return;
+    }
+
+    self.internal_credit_royalties(&account_id, &token_id, amount.0);
+  }
+}
+
+#[near_bindgen]
+impl Contract {
+  /// Withdraw all the royalties of the caller in `token_id`, or in NEAR when None. Requires exactly 1 yoctoNEAR.
+  #[payable]
+  pub fn withdraw_royalties(&mut self, token_id: Option<AccountId>) -> U128 {
+    assert_one_yocto();
+    let account_id = env::predecessor_account_id();
+    let amount = self.internal_take_royalties(&account_id, &token_id);
+    assert!(amount > 0, "Nothing to withdraw");
+
+    // Debit before the transfer. The callback credits it back if the transfer fails
+    let resolve = ext_self::ext(env::current_account_id())
+      .with_static_gas(GAS_FOR_RESOLVE_WITHDRAW)
+      .resolve_royalty_withdraw(account_id.clone(), token_id.clone(), U128(amount));
+    crate::fungible_token::transfer(&token_id, &account_id, amount).then(resolve);
+
+    U128(amount)
+  }
+
+  pub fn get_royalties(&self, account_id: AccountId) -> Vec<RoyaltyBalance> {
+    self.royalties.get(&account_id).unwrap_or_default()
+  }
+}
+
+impl Contract {
+  /// Credit `total_amount` of the fee token of a work to its members, by the ratios of the work or in equal
+  /// parts when it has none. The rounding remainder goes to the author.
+  pub(crate) fn internal_distribute_funds(&mut self, work: &Work, total_amount: Balance) {
+    let mut distribute_logs = Vec::new();
+    for (account_id, amount) in split_by_ratios(work, total_amount).into_iter().filter(|(_, amount)| *amount > 0) {
+      self.internal_credit_royalties(&account_id, &work.fee_token, amount);
+      distribute_logs.push(FundsDistributeLog {
+        work_id: work.id.clone(),
+        receiver_id: account_id,
+        amount: U128(amount),
+        token_id: work.fee_token.clone(),
+      });
+    }
+
+    if !distribute_logs.is_empty() {
+      EventLog::copyright(EventLogVariant::FundsDistribute(distribute_logs)).emit();
+    }
+  }
+
+  fn internal_credit_royalties(&mut self, account_id: &AccountId, token_id: &Option<AccountId>, amount: Balance) {
+    let mut balances = self.royalties.get(account_id).unwrap_or_default();
+    match balances.iter_mut().find(|balance| &balance.token_id == token_id) {
+      Some(balance) => balance.amount = U128(balance.amount.0 + amount),
+      None => balances.push(RoyaltyBalance { token_id: token_id.clone(), amount: U128(amount) }),
+    }
+    self.royalties.insert(account_id, &balances);
+  }
+
+  /// Set the royalties of an account in a token to zero and return them. The entry is kept, so crediting it
+  /// back takes no new storage.
+  fn internal_take_royalties(&mut self, account_id: &AccountId, token_id: &Option<AccountId>) -> Balance {
+    let mut balances = self.royalties.get(account_id).unwrap_or_default();
+    let amount = match balances.iter_mut().find(|balance| &balance.token_id == token_id) {
+      Some(balance) => std::mem::replace(&mut balance.amount, U128(0)).0,
+      None => return 0,
+    };
+    self.royalties.insert(account_id, &balances);
+    amount
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::test_utils::{accounts, VMContextBuilder};
+  use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig, ONE_NEAR};
+
+  use crate::test_utils::{create_song, set_caller};
+  use super::*;
+  use crate::Copyright;
+
+  fn royalties(contract: &Contract, account_id: AccountId) -> U128 {
+    contract.get_royalties(account_id).first().map(|balance| balance.amount).unwrap_or(U128(0))
+  }
+
+  #[test]
+  fn remainder_goes_to_the_author_and_failed_withdrawals_are_credited_back() {
+    set_caller(accounts(1), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    let work = create_song(&mut contract, Some(vec![accounts(3), accounts(4)]), Some(U128(10)), None);
+    for index in 3..5 {
+      set_caller(accounts(index), ONE_NEAR);
+      contract.accept_collaboration(work.id.clone());
+    }
+
+    set_caller(accounts(2), ONE_NEAR);
+    contract.get_access(work.id, vec![1; 32].into());
+    assert_eq!(royalties(&contract, accounts(1)), U128(4));
+    assert_eq!(royalties(&contract, accounts(3)), U128(3));
+    assert_eq!(royalties(&contract, accounts(4)), U128(3));
+
+    set_caller(accounts(1), 1);
+    assert_eq!(contract.withdraw_royalties(None), U128(4));
+    assert_eq!(royalties(&contract, accounts(1)), U128(0));
+
+    // The transfer failed
+    let mut context = VMContextBuilder::new();
+    context.current_account_id(accounts(0)).predecessor_account_id(accounts(0));
+    testing_env!(
+      context.build(),
+      VMConfig::test(),
+      RuntimeFeesConfig::test(),
+      Default::default(),
+      vec![PromiseResult::Failed]
+    );
+    contract.resolve_royalty_withdraw(accounts(1), None, U128(4));
+    assert_eq!(royalties(&contract, accounts(1)), U128(4));
+  }
+}
@@ -0,0 +1,27 @@
+use near_sdk::json_types::U128;
+use near_sdk::{near_bindgen, AccountId};
+
+use crate::storage::{StorageBalance, StorageBalanceBounds, StorageLedger, StorageManagement};
+
+use super::{Contract, ContractExt};
+
+#[near_bindgen]
+impl StorageManagement for Contract {
+  #[payable]
+  fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+    self.storage_balances.deposit(account_id, registration_only)
+  }
+
+  #[payable]
+  fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+    self.storage_balances.withdraw(amount)
+  }
+
+  fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+    self.storage_balances.balance_of(&account_id)
+  }
+
+  fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+    StorageLedger::bounds()
+  }
+}
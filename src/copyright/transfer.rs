@@ -0,0 +1,339 @@
+//! Assignment of the copyright of a work.
+//!
+//! The author of a work without collaborators offers it to another registered author with `transfer_work`,
+//! for free or for a price in yoctoNEAR. The offer of a shared work is made by a `TransferOwnership`
+//! proposal instead. The receiver takes it with `accept_work_transfer`, paying the price to the author, and
+//! becomes the author of the work. The receiver can call the offer off with `cancel_work_transfer`, and so
+//! can the author of a work without collaborators; a collaborator joining the work calls it off too. Every
+//! change of author, whether by an offer or a NEP-171 transfer, is appended to the chain of title of the
+//! work, which starts with its creation.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::Vector;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Promise, Timestamp};
+
+use crate::event::{EventLog, EventLogVariant, NftTransferLog, WorkTransferCancelLog, WorkTransferOfferLog};
+
+use super::collaboration::ratios_are_accepted;
+use super::{is_member, Contract, ContractExt, StorageKey, Work, WorkId};
+
+/// Offer of a work to `new_owner`, waiting for it to accept.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferOffer {
+  pub new_owner: AccountId,
+  /// Price in yoctoNEAR the new owner pays to the author. None for a free transfer.
+  pub price: Option<U128>,
+  pub memo: Option<String>,
+}
+
+/// One entry of the chain of title of a work. The first entry is the creation of the work and has no
+/// `previous_owner`.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TitleRecord {
+  pub owner_id: AccountId,
+  pub previous_owner: Option<AccountId>,
+  /// Price of the transfer when it was paid or reported.
+  pub price: Option<U128>,
+  pub memo: Option<String>,
+  /// Block time in milliseconds.
+  pub timestamp: Timestamp,
+}
+
+impl TitleRecord {
+  /// First entry of the chain of title: the author who registered the work.
+  pub(crate) fn origin(work: &Work) -> Self {
+    Self {
+      owner_id: work.author.id.clone(),
+      previous_owner: None,
+      price: None,
+      memo: None,
+      timestamp: work.published_date,
+    }
+  }
+}
+
+#[near_bindgen]
+impl Contract {
+  /// Offer a work to another author, replacing any previous offer. Only the author of a work without
+  /// collaborators can call this function.
+  #[payable]
+  pub fn transfer_work(&mut self, work_id: WorkId, new_owner: AccountId, price: Option<U128>, memo: Option<String>) {
+    let initial_storage = env::storage_usage();
+    let mut work = self.works.get(&work_id).expect("There is no work");
+    let owner_id = env::predecessor_account_id();
+    assert_eq!(work.author.id, owner_id, "Only the author can transfer a work");
+    assert!(work.collaborators.is_empty(), "A shared work is transferred by a TransferOwnership proposal");
+    self.assert_new_owner(&work, &new_owner);
+
+    work.pending_transfer = Some(TransferOffer { new_owner: new_owner.clone(), price, memo });
+    self.internal_save_work(&work);
+
+    EventLog::copyright(EventLogVariant::WorkTransferOffer(vec![WorkTransferOfferLog {
+      work_id,
+      owner_id,
+      new_owner_id: new_owner,
+      price,
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  /// Take a work offered to the caller. Attach at least the price: it goes to the author, and what is left
+  /// after storage is refunded.
+  #[payable]
+  pub fn accept_work_transfer(&mut self, work_id: WorkId) {
+    let initial_storage = env::storage_usage();
+    let work = self.works.get(&work_id).expect("There is no work");
+    let offer = work.pending_transfer.clone().expect("There is no transfer offer for this work");
+    let new_owner = env::predecessor_account_id();
+    assert_eq!(offer.new_owner, new_owner, "The work is not offered to you");
+
+    let price = offer.price.map(|price| price.0).unwrap_or(0);
+    let attached_deposit = env::attached_deposit();
+    assert!(attached_deposit >= price, "Attach at least {} yoctoNEAR to accept the transfer", price);
+
+    self.internal_assign_work(&work, &new_owner, None, offer.price, offer.memo);
+    if price > 0 {
+      Promise::new(work.author.id).transfer(price);
+    }
+
+    self.storage_balances.charge_caller(initial_storage, attached_deposit - price);
+  }
+
+  /// Call off the transfer offer of a work. Only the receiver of the offer and, for a work without
+  /// collaborators, the author can call this function.
+  #[payable]
+  pub fn cancel_work_transfer(&mut self, work_id: WorkId) {
+    let initial_storage = env::storage_usage();
+    let mut work = self.works.get(&work_id).expect("There is no work");
+    let offer = work.pending_transfer.take().expect("There is no transfer offer for this work");
+    let caller_id = env::predecessor_account_id();
+    assert!(
+      caller_id == work.author.id || caller_id == offer.new_owner,
+      "Only the author or the receiver can cancel a transfer"
+    );
+    assert!(
+      caller_id == offer.new_owner || work.collaborators.is_empty(),
+      "Only the receiver can cancel the transfer of a shared work"
+    );
+    self.internal_save_work(&work);
+
+    EventLog::copyright(EventLogVariant::WorkTransferCancel(vec![WorkTransferCancelLog {
+      work_id,
+      cancelled_by: caller_id,
+    }]))
+    .emit();
+
+    self.storage_balances.charge_caller(initial_storage, env::attached_deposit());
+  }
+
+  /// Owners of a work, from its creation to its current author.
+  pub fn get_chain_of_title(&self, work_id: WorkId, from_index: Option<u32>, limit: Option<u32>) -> Vec<TitleRecord> {
+    let titles = if let Some(titles) = self.titles.get(&work_id) { titles } else { return vec![] };
+
+    titles.iter().skip(from_index.unwrap_or(0) as usize).take(limit.unwrap_or(20) as usize).collect()
+  }
+}
+
+impl Contract {
+  /// Make `receiver_id`, a registered author, the author of a work and index the work under it. A
+  /// collaborator trades places with the previous author; anyone else takes over the ratio and the pending
+  /// ratio of the previous author. Approvals, the delivery agent and the transfer offer of the previous author are dropped.
+  pub(crate) fn internal_assign_work(
+    &mut self,
+    work: &Work,
+    receiver_id: &AccountId,
+    authorized_id: Option<AccountId>,
+    price: Option<U128>,
+    memo: Option<String>,
+  ) -> Work {
+    let receiver = self.authors.get(receiver_id).expect("The receiver is not an author");
+    let owner_id = work.author.id.clone();
+
+    let mut transferred = work.clone();
+    transferred.author = receiver;
+    match transferred.collaborators.iter().position(|account_id| account_id == receiver_id) {
+      Some(index) => transferred.collaborators[index] = owner_id.clone(),
+      None => {
+        for ratios in transferred.ratios.iter_mut().chain(transferred.pending_ratios.iter_mut()) {
+          rename_party(ratios, &owner_id, receiver_id);
+        }
+      },
+    }
+    transferred.approved_account_ids.clear();
+    transferred.invitations.retain(|invitation| &invitation.account_id != receiver_id);
+    if transferred.pending_ratios.as_ref().map(|ratios| ratios_are_accepted(&transferred, ratios)).unwrap_or(false) {
+      transferred.ratios = transferred.pending_ratios.take();
+    }
+    transferred.delivery_agent = None;
+    transferred.pending_transfer = None;
+    transferred.updated_date = env::block_timestamp_ms();
+
+    self.internal_remove_work(&owner_id, &work.id);
+    self.internal_save_work(&transferred);
+    self.internal_record_title(
+      &work.id,
+      TitleRecord {
+        owner_id: receiver_id.clone(),
+        previous_owner: Some(owner_id.clone()),
+        price,
+        memo: memo.clone(),
+        timestamp: transferred.updated_date,
+      },
+    );
+
+    EventLog::nft(EventLogVariant::NftTransfer(vec![NftTransferLog {
+      authorized_id,
+      old_owner_id: owner_id,
+      new_owner_id: receiver_id.clone(),
+      token_ids: vec![work.id.clone()],
+      memo,
+    }]))
+    .emit();
+
+    transferred
+  }
+
+  /// Append an owner to the chain of title of a work.
+  pub(crate) fn internal_record_title(&mut self, work_id: &WorkId, record: TitleRecord) {
+    let mut titles = self.titles.get(work_id).unwrap_or_else(|| {
+      Vector::new(
+        StorageKey::TitlesPerWorkInner { work_id_hash: env::sha256_array(work_id.as_bytes()) }.try_to_vec().unwrap(),
+      )
+    });
+    titles.push(&record);
+    self.titles.insert(work_id, &titles);
+  }
+
+  fn assert_new_owner(&self, work: &Work, new_owner: &AccountId) {
+    assert!(self.authors.get(new_owner).is_some(), "The new owner is not an author");
+    assert!(!is_member(work, new_owner), "The new owner is already a member of this work");
+  }
+}
+
+/// Hand the ratio of `from` over to `to`, adding it to the ratio `to` already has.
+fn rename_party(ratios: &mut Vec<(AccountId, u32)>, from: &AccountId, to: &AccountId) {
+  if !ratios.iter().any(|(account_id, _)| account_id == to) {
+    for (account_id, _) in ratios.iter_mut().filter(|(account_id, _)| account_id == from) {
+      *account_id = to.clone();
+    }
+    return;
+  }
+
+  let moved: u32 = ratios.iter().filter(|(account_id, _)| account_id == from).map(|(_, ratio)| *ratio).sum();
+  ratios.retain(|(account_id, _)| account_id != from);
+  if let Some((_, ratio)) = ratios.iter_mut().find(|(account_id, _)| account_id == to) {
+    *ratio += moved;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use near_sdk::test_utils::{accounts, get_logs};
+  use near_sdk::ONE_NEAR;
+
+  use crate::test_utils::{create_song, set_caller, transferred_deposits};
+  use super::super::{ProposalKind, ProposalStatus};
+  use super::*;
+  use crate::Copyright;
+
+  /// accounts(1) wrote a work and offered it to the author accounts(2) for 1 NEAR
+  fn setup() -> (Contract, WorkId) {
+    set_caller(accounts(2), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    contract.create_author("Bob".to_string(), 40);
+
+    let work = create_song(&mut contract, None, None, None);
+    contract.transfer_work(work.id.clone(), accounts(2), Some(U128(ONE_NEAR)), Some("Sale".to_string()));
+    (contract, work.id)
+  }
+
+  /// accounts(1) wrote a work with accounts(3), and invited accounts(4) with a 50/25/25 split
+  fn setup_shared() -> (Contract, WorkId) {
+    set_caller(accounts(2), ONE_NEAR);
+    let mut contract = Contract::new(accounts(0));
+    contract.create_author("Bob".to_string(), 40);
+
+    let collaborators = Some(vec![accounts(3), accounts(4)]);
+    let ratios = Some(vec![(accounts(1), 5_000), (accounts(3), 2_500), (accounts(4), 2_500)]);
+    let work = create_song(&mut contract, collaborators, None, ratios);
+
+    set_caller(accounts(3), ONE_NEAR);
+    contract.accept_collaboration(work.id.clone());
+    (contract, work.id)
+  }
+
+  #[test]
+  fn accepted_transfer_pays_the_author_and_extends_the_chain_of_title() {
+    let (mut contract, work_id) = setup();
+    set_caller(accounts(2), 2 * ONE_NEAR);
+    contract.accept_work_transfer(work_id.clone());
+
+    let paid = transferred_deposits();
+    assert_eq!(paid[0], (accounts(1), ONE_NEAR));
+    assert!(get_logs().iter().any(|log| log.contains(r#""standard":"nep171""#) && log.contains("nft_transfer")));
+
+    assert_eq!(contract.get_work_by_id(work_id.clone()).unwrap().author.id, accounts(2));
+    assert_eq!(contract.get_all_works_of_author(accounts(2)).len(), 1);
+    assert!(contract.get_all_works_of_author(accounts(1)).is_empty());
+
+    let titles = contract.get_chain_of_title(work_id, None, None);
+    assert_eq!(titles.len(), 2);
+    assert_eq!((titles[0].owner_id.clone(), titles[0].previous_owner.clone()), (accounts(1), None));
+    assert_eq!(titles[1].previous_owner, Some(accounts(1)));
+    assert_eq!(titles[1].price, Some(U128(ONE_NEAR)));
+    assert_eq!(titles[1].memo, Some("Sale".to_string()));
+  }
+
+  #[test]
+  #[should_panic(expected = "Attach at least 1000000000000000000000000 yoctoNEAR to accept the transfer")]
+  fn receiver_pays_the_price() {
+    let (mut contract, work_id) = setup();
+    set_caller(accounts(2), ONE_NEAR / 2);
+    contract.accept_work_transfer(work_id);
+  }
+
+  #[test]
+  #[should_panic(expected = "A shared work is transferred by a TransferOwnership proposal")]
+  fn author_can_not_transfer_a_shared_work() {
+    let (mut contract, work_id) = setup_shared();
+    set_caller(accounts(1), ONE_NEAR);
+    contract.transfer_work(work_id, accounts(2), None, None);
+  }
+
+  #[test]
+  fn passed_proposal_offers_a_shared_work() {
+    let (mut contract, work_id) = setup_shared();
+    set_caller(accounts(1), ONE_NEAR);
+    let proposal_id = contract.propose(work_id.clone(), ProposalKind::TransferOwnership { new_owner: accounts(2) });
+    set_caller(accounts(3), ONE_NEAR);
+    assert_eq!(contract.vote_on_proposal(proposal_id, true), ProposalStatus::Approved);
+
+    let work = contract.works.get(&work_id).unwrap();
+    assert_eq!(work.author.id, accounts(1));
+    assert_eq!(work.pending_transfer.map(|offer| offer.new_owner), Some(accounts(2)));
+
+    set_caller(accounts(2), ONE_NEAR);
+    contract.accept_work_transfer(work_id.clone());
+    let work = contract.works.get(&work_id).unwrap();
+    assert_eq!(work.author.id, accounts(2));
+    assert_eq!(work.collaborators, vec![accounts(3)]);
+    assert_eq!(work.pending_ratios, Some(vec![(accounts(2), 5_000), (accounts(3), 2_500), (accounts(4), 2_500)]));
+    assert_eq!(contract.get_chain_of_title(work_id, None, None).len(), 2);
+  }
+
+  #[test]
+  #[should_panic(expected = "There is no transfer offer for this work")]
+  fn cancelled_offer_can_not_be_accepted() {
+    let (mut contract, work_id) = setup();
+    set_caller(accounts(2), 0);
+    contract.cancel_work_transfer(work_id.clone());
+    set_caller(accounts(2), ONE_NEAR);
+    contract.accept_work_transfer(work_id);
+  }
+}
@@ -0,0 +1,442 @@
+//! NEP-297 events. Every event is logged as `EVENT_JSON:` followed by the serialized `EventLog`.
+
+use std::fmt;
+
+use near_sdk::{
+  env,
+  json_types::U128,
+  serde::{Deserialize, Serialize},
+  serde_json, AccountId,
+};
+
+/// Standard name and version of the NEP-171 events.
+pub const NFT_STANDARD_NAME: &str = "nep171";
+pub const NFT_METADATA_SPEC: &str = "1.0.0";
+
+/// Standard name and version of the copyright contract events.
+pub const COPYRIGHT_STANDARD_NAME: &str = "copyright";
+pub const COPYRIGHT_EVENT_VERSION: &str = "1.0.0";
+
+/// Standard name and version of the e-learning contract events.
+pub const ELEARNING_STANDARD_NAME: &str = "elearning";
+pub const ELEARNING_EVENT_VERSION: &str = "1.0.0";
+
+/// The `EventLog` struct is the envelope shared by every event.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventLog {
+  /// Name of the standard, e.g. "nep171".
+  pub standard: String,
+
+  /// Version of the standard, e.g. "1.0.0".
+  pub version: String,
+
+  /// The event name and its data.
+  #[serde(flatten)]
+  pub event: EventLogVariant,
+}
+
+/// The `EventLogVariant` enum lists every event and its data.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[serde(crate = "near_sdk::serde")]
+#[non_exhaustive]
+pub enum EventLogVariant {
+  NftMint(Vec<NftMintLog>),
+  NftTransfer(Vec<NftTransferLog>),
+  NftBurn(Vec<NftBurnLog>),
+
+  AuthorCreate(Vec<AuthorCreateLog>),
+  WorkCreate(Vec<WorkCreateLog>),
+  WorkUpdate(Vec<WorkUpdateLog>),
+  WorkDelete(Vec<WorkDeleteLog>),
+  WorkRate(Vec<WorkRateLog>),
+  WorkReport(Vec<WorkReportLog>),
+  WorkVote(Vec<WorkVoteLog>),
+  CollaborationInvite(Vec<CollaborationInviteLog>),
+  CollaborationAccept(Vec<CollaborationResponseLog>),
+  CollaborationDecline(Vec<CollaborationResponseLog>),
+  CollaborationLeave(Vec<CollaborationResponseLog>),
+  WorkTransferOffer(Vec<WorkTransferOfferLog>),
+  WorkTransferCancel(Vec<WorkTransferCancelLog>),
+  ProposalCreate(Vec<ProposalCreateLog>),
+  ProposalResolve(Vec<ProposalResolveLog>),
+  AccessPurchase(Vec<AccessPurchaseLog>),
+  FundsDistribute(Vec<FundsDistributeLog>),
+  RoyaltyWithdraw(Vec<RoyaltyWithdrawLog>),
+  ContentKeyDeliver(Vec<ContentKeyDeliverLog>),
+
+  CoursePurchase(Vec<CoursePurchaseLog>),
+  CourseComplete(Vec<CourseCompleteLog>),
+  LessonComplete(Vec<LessonCompleteLog>),
+  CourseRefund(Vec<CourseRefundLog>),
+  EscrowRelease(Vec<EscrowReleaseLog>),
+  SkillMint(Vec<SkillMintLog>),
+  RoleUpdate(Vec<RoleUpdateLog>),
+  InstructorApplication(Vec<InstructorApplicationLog>),
+  UserSuspend(Vec<UserSuspendLog>),
+  CourseDelist(Vec<CourseDelistLog>),
+  PoolCreate(Vec<PoolCreateLog>),
+  PoolWithdraw(Vec<PoolWithdrawLog>),
+}
+
+/// An event log to capture token minting.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftMintLog {
+  pub owner_id: AccountId,
+  pub token_ids: Vec<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub memo: Option<String>,
+}
+
+/// An event log to capture token transfer.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftTransferLog {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub authorized_id: Option<AccountId>,
+
+  pub old_owner_id: AccountId,
+  pub new_owner_id: AccountId,
+  pub token_ids: Vec<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub memo: Option<String>,
+}
+
+/// An event log to capture token burning.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftBurnLog {
+  pub owner_id: AccountId,
+  pub token_ids: Vec<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub authorized_id: Option<AccountId>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub memo: Option<String>,
+}
+
+/// An event log to capture author registration.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuthorCreateLog {
+  pub author_id: AccountId,
+  pub name: String,
+}
+
+/// An event log to capture work registration.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkCreateLog {
+  pub work_id: String,
+  pub author_id: AccountId,
+  pub collaborators: Vec<AccountId>,
+}
+
+/// An event log to capture a change of a work. `updated_fields` names the fields that changed.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkUpdateLog {
+  pub work_id: String,
+  pub editor_id: AccountId,
+  pub updated_fields: Vec<String>,
+}
+
+/// An event log to capture work removal.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkDeleteLog {
+  pub work_id: String,
+  pub author_id: AccountId,
+}
+
+/// An event log to capture a rating. A rating of 0 removes the previous one.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkRateLog {
+  pub work_id: String,
+  pub rater_id: AccountId,
+  pub rating: u32,
+}
+
+/// An event log to capture an infringement report.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkReportLog {
+  pub work_id: String,
+  pub reporter_id: AccountId,
+  pub reason: String,
+}
+
+/// An event log to capture a vote on a proposal of a work.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkVoteLog {
+  pub work_id: String,
+  pub proposal_id: u64,
+  pub voter_id: AccountId,
+  pub decision: bool,
+}
+
+/// An event log to capture an invitation to collaborate on a work. `expires_at` is in milliseconds.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollaborationInviteLog {
+  pub work_id: String,
+  pub inviter_id: AccountId,
+  pub invitee_id: AccountId,
+  pub expires_at: u64,
+}
+
+/// An event log to capture an invited account accepting or declining to collaborate on a work, or a
+/// collaborator leaving it.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollaborationResponseLog {
+  pub work_id: String,
+  pub account_id: AccountId,
+}
+
+/// An event log to capture an offer of a work to a new author. The transfer itself is an `nft_transfer` event.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkTransferOfferLog {
+  pub work_id: String,
+  pub owner_id: AccountId,
+  pub new_owner_id: AccountId,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub price: Option<U128>,
+}
+
+/// An event log to capture a transfer offer called off by the author or the receiver.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkTransferCancelLog {
+  pub work_id: String,
+  pub cancelled_by: AccountId,
+}
+
+/// An event log to capture a new proposal on a work. `kind` is the snake case name of the proposal kind.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalCreateLog {
+  pub work_id: String,
+  pub proposal_id: u64,
+  pub proposer_id: AccountId,
+  pub kind: String,
+}
+
+/// An event log to capture a proposal that passed and was executed, or was rejected.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalResolveLog {
+  pub work_id: String,
+  pub proposal_id: u64,
+  pub approved: bool,
+}
+
+/// An event log to capture a purchase of the access to a work.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccessPurchaseLog {
+  pub work_id: String,
+  pub buyer_id: AccountId,
+  pub amount: U128,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub token_id: Option<AccountId>,
+}
+
+/// An event log to capture the share of the funds of a work credited to the royalty balance of one account.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundsDistributeLog {
+  pub work_id: String,
+  pub receiver_id: AccountId,
+  pub amount: U128,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub token_id: Option<AccountId>,
+}
+
+/// An event log to capture an account withdrawing its royalty balance.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoyaltyWithdrawLog {
+  pub account_id: AccountId,
+  pub amount: U128,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub token_id: Option<AccountId>,
+}
+
+/// An event log to capture a content key delivered to a buyer of a work.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContentKeyDeliverLog {
+  pub work_id: String,
+  pub account_id: AccountId,
+  pub delivered_by: AccountId,
+}
+
+/// An event log to capture a course enrollment.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CoursePurchaseLog {
+  pub course_id: String,
+  pub student_id: AccountId,
+  pub amount: U128,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub token_id: Option<AccountId>,
+}
+
+/// An event log to capture a student leaving a course with a refund.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CourseRefundLog {
+  pub course_id: String,
+  pub student_id: AccountId,
+  pub amount: U128,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub token_id: Option<AccountId>,
+}
+
+/// An event log to capture escrowed payments of a course released to the instructor.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowReleaseLog {
+  pub course_id: String,
+  pub student_ids: Vec<AccountId>,
+  pub amount: U128,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub token_id: Option<AccountId>,
+}
+
+/// An event log to capture a student completing a course.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CourseCompleteLog {
+  pub course_id: String,
+  pub student_id: AccountId,
+}
+
+/// An event log to capture a student completing a lesson of a course.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LessonCompleteLog {
+  pub course_id: String,
+  pub student_id: AccountId,
+  pub lessons_completed: u32,
+}
+
+/// An event log to capture skill credit minted from a certificate.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SkillMintLog {
+  pub skill_id: String,
+  pub owner_id: AccountId,
+  pub credit: u32,
+  pub certificate_id: String,
+}
+
+/// An event log to capture a role granted to or revoked from a user. `role` is the new role.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoleUpdateLog {
+  pub user_id: AccountId,
+  pub role: String,
+  pub updated_by: AccountId,
+}
+
+/// An event log to capture an instructor application being submitted or reviewed. `status` is the new status.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InstructorApplicationLog {
+  pub user_id: AccountId,
+  pub status: String,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub reviewer_id: Option<AccountId>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub reason: Option<String>,
+}
+
+/// An event log to capture a user being suspended or reinstated.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UserSuspendLog {
+  pub user_id: AccountId,
+  pub suspended: bool,
+  pub updated_by: AccountId,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub reason: Option<String>,
+}
+
+/// An event log to capture a course being delisted or listed again.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CourseDelistLog {
+  pub course_id: String,
+  pub delisted: bool,
+  pub updated_by: AccountId,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub reason: Option<String>,
+}
+
+/// An event log to capture the creation of a revenue pool.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolCreateLog {
+  pub pool_id: String,
+  pub creator_id: AccountId,
+  pub members: Vec<AccountId>,
+}
+
+/// An event log to capture a member withdrawing its balance from a pool.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolWithdrawLog {
+  pub pool_id: String,
+  pub account_id: AccountId,
+  pub amount: U128,
+}
+
+impl EventLog {
+  /// Build a NEP-171 event.
+  pub fn nft(event: EventLogVariant) -> Self {
+    Self { standard: NFT_STANDARD_NAME.to_string(), version: NFT_METADATA_SPEC.to_string(), event }
+  }
+
+  /// Build an event of the copyright contract.
+  pub fn copyright(event: EventLogVariant) -> Self {
+    Self { standard: COPYRIGHT_STANDARD_NAME.to_string(), version: COPYRIGHT_EVENT_VERSION.to_string(), event }
+  }
+
+  /// Build an event of the e-learning contract.
+  pub fn elearning(event: EventLogVariant) -> Self {
+    Self { standard: ELEARNING_STANDARD_NAME.to_string(), version: ELEARNING_EVENT_VERSION.to_string(), event }
+  }
+
+  /// Write the event to the receipt logs.
+  pub fn emit(&self) {
+    env::log_str(&self.to_string());
+  }
+}
+
+impl fmt::Display for EventLog {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_fmt(format_args!("EVENT_JSON:{}", &serde_json::to_string(self).map_err(|_| fmt::Error)?))
+  }
+}
@@ -0,0 +1,82 @@
+//! NEP-141 payments shared by both contracts.
+//!
+//! A buyer calls `ft_transfer_call` on an accepted token contract with this contract as receiver and a JSON
+//! `msg` naming the purchase. The token contract calls `ft_on_transfer`, which makes the purchase with the
+//! transferred tokens and returns the unused amount, which the token contract sends back to the buyer. If the
+//! purchase fails, the whole amount goes back. Payouts in tokens are `ft_transfer` calls.
+
+use near_sdk::{
+  borsh::{self, BorshDeserialize, BorshSerialize},
+  collections::UnorderedSet,
+  env, ext_contract,
+  json_types::U128,
+  AccountId, Balance, Gas, IntoStorageKey, Promise, PromiseOrValue,
+};
+
+/// Gas attached to every `ft_transfer` payout.
+pub const GAS_FOR_FT_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+/// Receiver side of NEP-141 `ft_transfer_call`.
+pub trait FungibleTokenReceiver {
+  /// Make the purchase described by `msg` with `amount` tokens of the calling token contract, sent by
+  /// `sender_id`. Returns the amount of tokens to give back to `sender_id`.
+  fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128>;
+}
+
+#[ext_contract(ext_ft)]
+pub trait FungibleTokenCore {
+  fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+/// Pay `amount` of `token_id` to `receiver_id`, or of NEAR when `token_id` is None.
+pub fn transfer(token_id: &Option<AccountId>, receiver_id: &AccountId, amount: Balance) -> Promise {
+  match token_id {
+    Some(token_id) => ext_ft::ext(token_id.clone())
+      .with_attached_deposit(1)
+      .with_static_gas(GAS_FOR_FT_TRANSFER)
+      .ft_transfer(receiver_id.clone(), U128(amount), None),
+    None => Promise::new(receiver_id.clone()).transfer(amount),
+  }
+}
+
+/// Token contracts accepted for payments.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TokenWhitelist {
+  tokens: UnorderedSet<AccountId>,
+}
+
+impl TokenWhitelist {
+  pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
+    Self { tokens: UnorderedSet::new(prefix) }
+  }
+
+  pub fn contains(&self, token_id: &AccountId) -> bool {
+    self.tokens.contains(token_id)
+  }
+
+  /// Panic unless `token_id` is None (NEAR) or an accepted token.
+  pub fn assert_accepted(&self, token_id: &Option<AccountId>) {
+    if let Some(token_id) = token_id {
+      assert!(self.contains(token_id), "The token {} is not accepted", token_id);
+    }
+  }
+
+  /// Panic unless the predecessor is an accepted token contract, and return it.
+  pub fn assert_caller_accepted(&self) -> AccountId {
+    let token_id = env::predecessor_account_id();
+    assert!(self.contains(&token_id), "The token {} is not accepted", token_id);
+    token_id
+  }
+
+  pub fn insert(&mut self, token_id: &AccountId) -> bool {
+    self.tokens.insert(token_id)
+  }
+
+  pub fn remove(&mut self, token_id: &AccountId) -> bool {
+    self.tokens.remove(token_id)
+  }
+
+  pub fn to_vec(&self) -> Vec<AccountId> {
+    self.tokens.to_vec()
+  }
+}
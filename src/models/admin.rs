@@ -0,0 +1,64 @@
+use near_sdk::AccountId;
+
+use super::{
+  certificate::CertificateId,
+  course::CourseId,
+  user::{JsonUser, Roles, UserId},
+};
+
+/// Highest platform fee, 50% in basis points.
+pub const MAX_PLATFORM_FEE: u32 = 5_000;
+
+/// The `AdminFeatures` trait defines what the contract owner, admins and managers can do to other users.
+///
+/// The contract owner always acts as an Admin and is the only one who grants or revokes the Admin role.
+/// Admins have power over every other user. A Manager represents a partner organization: it grants the
+/// Instructor role and only has power over the instructors it granted the role to, and their courses.
+pub trait AdminFeatures {
+  /// Give `role` to a user. Admin: owner only. Manager: admins. Instructor: admins and managers
+  fn grant_role(&mut self, user_id: UserId, role: Roles) -> JsonUser;
+
+  /// Make a user a Subscriber again. Revoking an Instructor also works for its manager
+  fn revoke_role(&mut self, user_id: UserId) -> JsonUser;
+
+  /// Forbid a user to change its information and to create, buy or grade courses
+  fn suspend_user(&mut self, user_id: UserId, reason: String);
+
+  /// Lift the suspension of a user
+  fn unsuspend_user(&mut self, user_id: UserId);
+
+  /// Stop the sale of a course. Students who already bought it keep it
+  fn delist_course(&mut self, course_id: CourseId, reason: String);
+
+  /// Put a delisted course back on sale
+  fn relist_course(&mut self, course_id: CourseId);
+
+  /// Burn a certificate. The skill credit minted from it is taken back
+  fn revoke_certificate(&mut self, certificate_id: CertificateId, reason: String);
+
+  /// Set the part of every course sale paid to the owner, in basis points. Only the owner can call this function
+  fn set_platform_fee(&mut self, platform_fee: u32);
+
+  /// Get the platform fee in basis points
+  fn get_platform_fee(&self) -> u32;
+
+  /// Accept a NEP-141 token contract for course prices. Only an admin can call this function
+  fn add_accepted_token(&mut self, token_id: AccountId) -> bool;
+
+  /// Stop accepting a token contract for new course prices. Only an admin can call this function
+  fn remove_accepted_token(&mut self, token_id: AccountId) -> bool;
+
+  /// Get the accepted token contracts
+  fn get_accepted_tokens(&self) -> Vec<AccountId>;
+
+  /// Check the account is the owner or an Admin
+  fn is_admin(&self, account_id: UserId) -> bool;
+
+  /// Get the instructors of a manager. From 'index' to 'index + limit'
+  fn get_instructors_per_manager(
+    &self,
+    manager_id: UserId,
+    from_index: Option<u32>,
+    limit: Option<u32>,
+  ) -> Vec<JsonUser>;
+}
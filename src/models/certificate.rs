@@ -19,6 +19,9 @@ pub struct CertificateMetadata {
   /// Student own the certificate. Course mint certificate and send to student
   pub student: UserId,
 
+  /// Course the certificate was issued for
+  pub course_id: CourseId,
+
   /// Skill user own after complete the course
   pub skill_id: SkillId,
 
@@ -33,6 +36,9 @@ pub struct CertificateMetadata {
 
   /// Optional description of the certificate.
   pub description: Option<String>,
+
+  /// Date when the certificate was minted, represented as a timestamp.
+  pub issued_at: u64,
 }
 
 pub trait CertificateFeatures {
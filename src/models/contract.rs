@@ -1,18 +1,23 @@
 use near_sdk::{
+  borsh::{self, BorshDeserialize, BorshSerialize},
   collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet},
   json_types::Base64VecU8,
   near_bindgen,
   serde::{Deserialize, Serialize},
-  AccountId, CryptoHash, PanicOnDefault,
+  AccountId, Balance, CryptoHash, PanicOnDefault,
 };
 
-use crate::borsh::{self, BorshDeserialize, BorshSerialize};
+use crate::fungible_token::TokenWhitelist;
+use crate::storage::StorageLedger;
 
 use super::{
   certificate::{CertificateId, CertificateMetadata},
-  course::{CourseId, CourseMetadata},
+  course::{CourseEscrow, CourseId, CourseMetadata},
+  instructor::InstructorApplication,
+  pool::{PoolId, PoolMetadata},
   skill::{SkillId, SkillMetadata},
   user::{JsonUser, UserId},
+  version::StateVersion,
 };
 
 /// The `ELearningContractMetadata` struct represents metadata for an e-learning contract.
@@ -78,8 +83,77 @@ pub struct ELearningContract {
   /// Map of `CertificateMetadata` by certificate ID.
   pub certificate_metadata_by_id: LookupMap<CertificateId, CertificateMetadata>,
 
+  /// Storage all certificate_id minted -> For count all of certificates (NFT total supply)
+  pub all_certificates: UnorderedSet<CertificateId>,
+
   /// Map of SkillMetadata by SkillId
   pub skill_metadata_by_skill_id: LookupMap<SkillId, UnorderedSet<SkillMetadata>>,
+
+  /// Set of instructors per manager. Managers can only act on the instructors they granted the role to
+  pub instructors_per_manager: LookupMap<UserId, UnorderedSet<UserId>>,
+
+  /// Map of the last instructor application by user ID.
+  pub instructor_applications: LookupMap<UserId, InstructorApplication>,
+
+  /// Storage all user_id with a pending instructor application -> For the review queue
+  pub pending_applications: UnorderedSet<UserId>,
+
+  /// Map of `PoolMetadata` by pool ID.
+  pub pool_metadata_by_id: LookupMap<PoolId, PoolMetadata>,
+
+  /// Map of pool sets by member
+  pub pools_per_user: LookupMap<UserId, UnorderedSet<PoolId>>,
+
+  /// Payments held until the refund period of their course ends, by course ID and student
+  pub escrows_per_course: LookupMap<CourseId, UnorderedMap<UserId, CourseEscrow>>,
+
+  /// Part of every course sale paid to the owner, in basis points (1/100 of a percent)
+  pub platform_fee: u32,
+
+  /// Course payouts whose transfer failed, by receiver and token (None for NEAR), until `withdraw_payout`
+  pub payout_balances: LookupMap<(UserId, Option<AccountId>), Balance>,
+
+  /// NEP-141 token contracts a course price can be set in
+  pub accepted_tokens: TokenWhitelist,
+
+  /// NEP-145 storage balances of the accounts that pay for their storage in advance
+  pub storage_balances: StorageLedger,
+}
+
+#[near_bindgen]
+impl ELearningContract {
+  /// Initialize the contract. Every persistent collection gets its own prefix from `ContractStorageKey`.
+  /// The current state version is recorded for later layout changes.
+  #[init]
+  pub fn new(owner_id: AccountId, metadata: ELearningContractMetadata) -> Self {
+    StateVersion::CURRENT.write();
+
+    Self {
+      owner_id,
+      metadata_contract: LazyOption::new(ContractStorageKey::ContractMetadata.try_to_vec().unwrap(), Some(&metadata)),
+      subscriber_users: UnorderedSet::new(ContractStorageKey::SubscriberUsers.try_to_vec().unwrap()),
+      intructor_users: UnorderedSet::new(ContractStorageKey::IntructorUsers.try_to_vec().unwrap()),
+      mentor_users: UnorderedMap::new(ContractStorageKey::MentorUsers.try_to_vec().unwrap()),
+      user_metadata_by_id: LookupMap::new(ContractStorageKey::UserMetadataById.try_to_vec().unwrap()),
+      courses_per_user: LookupMap::new(ContractStorageKey::CoursesPerUser.try_to_vec().unwrap()),
+      courses_per_instructor: LookupMap::new(ContractStorageKey::CoursesPerInstructor.try_to_vec().unwrap()),
+      course_metadata_by_id: LookupMap::new(ContractStorageKey::CourseMetadataById.try_to_vec().unwrap()),
+      certificate_per_user: LookupMap::new(ContractStorageKey::CertificatesPerUser.try_to_vec().unwrap()),
+      certificate_metadata_by_id: LookupMap::new(ContractStorageKey::CertificateMetadataById.try_to_vec().unwrap()),
+      all_certificates: UnorderedSet::new(ContractStorageKey::AllCertificates.try_to_vec().unwrap()),
+      skill_metadata_by_skill_id: LookupMap::new(ContractStorageKey::SkillMetadataPerSkillId.try_to_vec().unwrap()),
+      instructors_per_manager: LookupMap::new(ContractStorageKey::InstructorsPerManager.try_to_vec().unwrap()),
+      instructor_applications: LookupMap::new(ContractStorageKey::InstructorApplications.try_to_vec().unwrap()),
+      pending_applications: UnorderedSet::new(ContractStorageKey::PendingApplications.try_to_vec().unwrap()),
+      pool_metadata_by_id: LookupMap::new(ContractStorageKey::PoolMetadataById.try_to_vec().unwrap()),
+      pools_per_user: LookupMap::new(ContractStorageKey::PoolsPerUser.try_to_vec().unwrap()),
+      escrows_per_course: LookupMap::new(ContractStorageKey::EscrowsPerCourse.try_to_vec().unwrap()),
+      payout_balances: LookupMap::new(ContractStorageKey::PayoutBalances.try_to_vec().unwrap()),
+      platform_fee: 0,
+      accepted_tokens: TokenWhitelist::new(ContractStorageKey::AcceptedTokens.try_to_vec().unwrap()),
+      storage_balances: StorageLedger::new(ContractStorageKey::StorageBalances.try_to_vec().unwrap()),
+    }
+  }
 }
 
 /// The `ContractStorageKey` enum represents keys for different persistent collections in the contract storage.
@@ -99,4 +173,17 @@ pub enum ContractStorageKey {
   CoursesPerInstructorInner { instructor_id_hash: CryptoHash },
   SkillMetadataPerSkillId,
   SkillMetadataPerSkillIdInner { skill_id_hash: CryptoHash },
+  AllCertificates,
+  StorageBalances,
+  InstructorsPerManager,
+  InstructorsPerManagerInner { manager_id_hash: CryptoHash },
+  InstructorApplications,
+  PendingApplications,
+  PoolMetadataById,
+  PoolsPerUser,
+  PoolsPerUserInner { account_id_hash: CryptoHash },
+  EscrowsPerCourse,
+  EscrowsPerCourseInner { course_id_hash: CryptoHash },
+  AcceptedTokens,
+  PayoutBalances,
 }
@@ -2,11 +2,13 @@ use std::collections::HashMap;
 
 use near_sdk::{
   borsh::{self, BorshDeserialize, BorshSerialize},
+  json_types::U128,
   serde::{Deserialize, Serialize},
-  AccountId, Balance,
+  AccountId,
 };
 
-use super::user::UserId;
+use super::{pool::PoolId, user::UserId};
+use crate::content::ContentRef;
 
 /// `CourseId` is a type alias for `String`, typically representing a unique identifier for a course in the system.
 pub type CourseId = String;
@@ -34,7 +36,10 @@ pub struct CourseMetadata {
   pub created_at: u64,
 
   /// Price of this course, of type `U128`.
-  pub price: Balance,
+  pub price: U128,
+
+  /// Token contract the price is paid in through `ft_transfer_call`, or None for NEAR
+  pub price_token: Option<AccountId>,
 
   /// Number of students currently studying this course.
   pub students_studying_map: HashMap<AccountId, u64>,
@@ -42,23 +47,69 @@ pub struct CourseMetadata {
   /// Number of students who have completed this course. And time stamp
   pub students_completed: HashMap<AccountId, u64>,
 
+  /// Number of lessons each student has completed, recorded by the instructor
+  pub lessons_completed: HashMap<AccountId, u32>,
+
   /// Average of all the ratings this course has received.
   pub rating: u8,
 
   /// Number of ratings this course has received.
   pub rating_count: u32,
 
-  /// The Content of this course
-  pub content: String,
+  /// Off-chain location of the content of this course. None until the instructor uploads it
+  pub content: Option<ContentRef>,
+
+  /// Certificates of this course can be transferred. Default is false (soulbound)
+  pub certificate_transferable: bool,
+
+  /// Delisted courses can not be bought. Set by an admin or the manager of the instructor
+  pub delisted: bool,
+
+  /// Pool that receives a share of every sale of this course, if any
+  pub pool_id: Option<PoolId>,
+
+  /// Milliseconds after enrollment during which a student can ask for a refund. The payment is held in escrow
+  /// until then. 0 pays the instructor at once
+  pub refund_period: u64,
+}
+
+/// The `CourseEscrow` struct represents a course payment held until the refund period ends.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CourseEscrow {
+  /// Student who paid.
+  pub student_id: UserId,
+
+  /// Price paid for the course, in the smallest unit of `token_id`.
+  pub amount: U128,
+
+  /// Token contract the course was paid in, or None for NEAR.
+  pub token_id: Option<AccountId>,
+
+  /// Platform fee in basis points when the course was bought.
+  pub platform_fee: u32,
+
+  /// Pool of the course when it was bought, which receives its share on release.
+  pub pool_id: Option<PoolId>,
+
+  /// Date after which the payment can be released, represented as a timestamp.
+  pub release_at: u64,
 }
 
+/// Refund period of new courses, 7 days in milliseconds.
+pub const DEFAULT_REFUND_PERIOD: u64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Longest refund period, 30 days in milliseconds.
+pub const MAX_REFUND_PERIOD: u64 = 30 * 24 * 60 * 60 * 1000;
+
 pub trait CourseFeatures {
   fn create_course(
     &mut self,
     title: String,
     description: Option<String>,
     media: Option<String>,
-    price: Balance,
+    price: U128,
+    content: Option<ContentRef>,
   ) -> CourseMetadata;
   fn payment_course(&mut self, course_id: CourseId);
   fn get_course_metadata_by_course_id(&self, course_id: CourseId) -> Option<CourseMetadata>;
@@ -82,4 +133,45 @@ pub trait CourseFeatures {
 
   /// Check user completed course or not
   fn check_course_completed(&self, course_id: CourseId, user_id: UserId) -> bool;
+
+  /// Record that a student completed one more lesson of a course. Returns the lessons completed. Only course
+  /// owner can call this function
+  fn complete_lesson(&mut self, course_id: CourseId, user_id: UserId) -> u32;
+
+  /// Allow or forbid transfers of the certificates of a course. Only course owner can call this function
+  fn set_certificate_transferable(&mut self, course_id: CourseId, transferable: bool);
+
+  /// Replace the content of a course. Only course owner can call this function
+  fn set_course_content(&mut self, course_id: CourseId, content: ContentRef);
+
+  /// Set the token contract the price of a course is paid in, or None for NEAR. The token must be accepted,
+  /// the course must not be in a pool and have no escrowed payment. Only course owner can call this function
+  fn set_price_token(&mut self, course_id: CourseId, price_token: Option<AccountId>);
+
+  /// Change the refund period of the next purchases of a course. Only course owner can call this function
+  fn set_refund_period(&mut self, course_id: CourseId, refund_period: u64);
+
+  /// Leave a course and get the payment back. Only before the refund period ends and any lesson is completed
+  fn request_refund(&mut self, course_id: CourseId);
+
+  /// Callback of `request_refund`. Restore the escrow and the enrollment if the transfer failed
+  fn resolve_refund(&mut self, course_id: CourseId, escrow: CourseEscrow, enrolled_at: u64);
+
+  /// Pay the instructor the escrowed payments of a course whose refund period ended or whose student completed
+  /// the course, at most `limit` of them. Returns the total released. Only course owner can call this function
+  fn release_escrow(&mut self, course_id: CourseId, limit: Option<u32>) -> U128;
+
+  /// Callback of the payouts of course payments. Credit the payout to the payout balance of its receiver if
+  /// the transfer failed
+  fn resolve_payout(&mut self, receiver_id: AccountId, token_id: Option<AccountId>, amount: U128);
+
+  /// Send the caller its payout balance in `token_id`, or NEAR for None. Returns the amount sent. Requires
+  /// exactly 1 yoctoNEAR
+  fn withdraw_payout(&mut self, token_id: Option<AccountId>) -> U128;
+
+  /// Get the course payouts of an account in `token_id`, or NEAR for None, whose transfer failed
+  fn get_payout_balance(&self, account_id: AccountId, token_id: Option<AccountId>) -> U128;
+
+  /// Get the escrowed payments of a course. From 'start' to 'start + limit'
+  fn get_course_escrows(&self, course_id: CourseId, start: Option<u32>, limit: Option<u32>) -> Vec<CourseEscrow>;
 }
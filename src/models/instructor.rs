@@ -0,0 +1,88 @@
+use near_sdk::{
+  borsh::{self, BorshDeserialize, BorshSerialize},
+  serde::{Deserialize, Serialize},
+};
+
+use super::user::UserId;
+use crate::content::ContentRef;
+
+/// Most categories an application can request.
+pub const MAX_APPLICATION_CATEGORIES: usize = 5;
+
+/// Longest accepted category name.
+pub const MAX_CATEGORY_LEN: usize = 32;
+
+/// Longest accepted portfolio link.
+pub const MAX_PORTFOLIO_LEN: usize = 512;
+
+/// The `ApplicationStatus` enum represents where an instructor application is in the review.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ApplicationStatus {
+  /// Waiting for an admin or a manager.
+  Pending,
+  /// The user became an Instructor.
+  Approved,
+  /// The user stays a Subscriber and can apply again.
+  Rejected,
+}
+
+/// The `InstructorApplication` struct is the request of a user to become an Instructor.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InstructorApplication {
+  /// User who applied.
+  pub user_id: UserId,
+
+  /// Off-chain location of the resume.
+  pub resume: ContentRef,
+
+  /// Link to the portfolio of the user, if provided.
+  pub portfolio: Option<String>,
+
+  /// Categories the user wants to teach.
+  pub categories: Vec<String>,
+
+  /// Status of the review.
+  pub status: ApplicationStatus,
+
+  /// Admin or manager who reviewed the application. None while it is pending
+  pub reviewer_id: Option<UserId>,
+
+  /// Reason given by the reviewer. Always set when the application is rejected
+  pub reason: Option<String>,
+
+  /// Date when the application was submitted, represented as a timestamp.
+  pub submitted_at: u64,
+
+  /// Date when the application was reviewed, represented as a timestamp.
+  pub reviewed_at: Option<u64>,
+}
+
+/// The `InstructorApplicationFeatures` trait defines how a Subscriber becomes an Instructor.
+pub trait InstructorApplicationFeatures {
+  /// Apply to become an Instructor. Replaces a rejected application. Only Subscribers can apply
+  fn apply_for_instructor(
+    &mut self,
+    resume: ContentRef,
+    portfolio: Option<String>,
+    categories: Vec<String>,
+  ) -> InstructorApplication;
+
+  /// Approve a pending application. The applicant becomes an Instructor, managed by the reviewer if it is a
+  /// manager. Only admins and managers can call this function
+  fn approve_instructor_application(&mut self, user_id: UserId, reason: Option<String>) -> InstructorApplication;
+
+  /// Reject a pending application. Only admins and managers can call this function
+  fn reject_instructor_application(&mut self, user_id: UserId, reason: String) -> InstructorApplication;
+
+  /// Get the last application of a user
+  fn get_instructor_application(&self, user_id: UserId) -> Option<InstructorApplication>;
+
+  /// Get the pending applications. From 'index' to 'index + limit'
+  fn get_pending_instructor_applications(
+    &self,
+    from_index: Option<u32>,
+    limit: Option<u32>,
+  ) -> Vec<InstructorApplication>;
+}
@@ -1,5 +1,10 @@
+pub mod admin;
 pub mod certificate;
 pub mod contract;
 pub mod course;
+pub mod instructor;
+pub mod nft;
+pub mod pool;
 pub mod skill;
 pub mod user;
+pub mod version;
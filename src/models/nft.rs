@@ -0,0 +1,116 @@
+use near_sdk::{
+  json_types::{Base64VecU8, U128},
+  serde::{Deserialize, Serialize},
+  AccountId, PromiseOrValue,
+};
+
+use super::{certificate::CertificateId, contract::ELearningContractMetadata};
+
+/// `TokenId` is the NEP-171 name of a `CertificateId`. Every certificate is a token.
+pub type TokenId = CertificateId;
+
+/// The `TokenMetadata` struct is the NEP-177 metadata of a certificate token.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMetadata {
+  /// Name of the course the certificate was issued for.
+  pub title: Option<String>,
+
+  /// Description of the certificate.
+  pub description: Option<String>,
+
+  /// The certificate link.
+  pub media: Option<String>,
+
+  /// Base64-encoded sha256 hash of the content referenced by `media`.
+  pub media_hash: Option<Base64VecU8>,
+
+  /// Certificates are unique, so this is always 1.
+  pub copies: Option<u64>,
+
+  /// Unix epoch in milliseconds when the certificate was minted.
+  pub issued_at: Option<String>,
+
+  /// When the token expires.
+  pub expires_at: Option<String>,
+
+  /// When the token starts being valid.
+  pub starts_at: Option<String>,
+
+  /// When the token was last updated.
+  pub updated_at: Option<String>,
+
+  /// JSON string with the course, skill and credit of the certificate.
+  pub extra: Option<String>,
+
+  /// URL to an off-chain JSON file with more info.
+  pub reference: Option<String>,
+
+  /// Base64-encoded sha256 hash of JSON from the reference field.
+  pub reference_hash: Option<Base64VecU8>,
+}
+
+/// The `JsonToken` struct is the NEP-171 view of a certificate.
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonToken {
+  /// Id of the token, the certificate id.
+  pub token_id: TokenId,
+
+  /// Current owner of the certificate.
+  pub owner_id: AccountId,
+
+  /// Metadata of the token.
+  pub metadata: TokenMetadata,
+}
+
+/// NEP-171 core functions. Certificates are soulbound unless their course allows transfers.
+pub trait NonFungibleTokenCore {
+  /// Transfer a certificate to another registered user. Requires exactly 1 yoctoNEAR.
+  fn nft_transfer(&mut self, receiver_id: AccountId, token_id: TokenId, approval_id: Option<u64>, memo: Option<String>);
+
+  /// Transfer a certificate and call `nft_on_transfer` on the receiver. Requires exactly 1 yoctoNEAR.
+  fn nft_transfer_call(
+    &mut self,
+    receiver_id: AccountId,
+    token_id: TokenId,
+    approval_id: Option<u64>,
+    memo: Option<String>,
+    msg: String,
+  ) -> PromiseOrValue<bool>;
+
+  /// Get the token of a certificate
+  fn nft_token(&self, token_id: TokenId) -> Option<JsonToken>;
+}
+
+/// Callback of `nft_transfer_call`. Give the certificate back when the receiver asks for it.
+pub trait NonFungibleTokenResolver {
+  fn nft_resolve_transfer(
+    &mut self,
+    owner_id: AccountId,
+    receiver_id: AccountId,
+    token_id: TokenId,
+    memo: Option<String>,
+  ) -> bool;
+}
+
+/// NEP-181 enumeration functions.
+pub trait NonFungibleTokenEnumeration {
+  /// Number of certificates minted by the contract
+  fn nft_total_supply(&self) -> U128;
+
+  /// Get all tokens. From 'index' to 'index + limit'
+  fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken>;
+
+  /// Number of certificates owned by an account
+  fn nft_supply_for_owner(&self, account_id: AccountId) -> U128;
+
+  /// Get tokens owned by an account. From 'index' to 'index + limit'
+  fn nft_tokens_for_owner(&self, account_id: AccountId, from_index: Option<U128>, limit: Option<u64>)
+    -> Vec<JsonToken>;
+}
+
+/// NEP-177 contract metadata.
+pub trait NonFungibleTokenMetadata {
+  fn nft_metadata(&self) -> ELearningContractMetadata;
+}
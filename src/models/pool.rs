@@ -0,0 +1,83 @@
+use near_sdk::{
+  borsh::{self, BorshDeserialize, BorshSerialize},
+  json_types::U128,
+  serde::{Deserialize, Serialize},
+};
+
+use super::{course::CourseId, user::UserId};
+
+/// `PoolId` is a type alias for `String`, typically representing a unique identifier for a pool in the system.
+pub type PoolId = String;
+
+/// Most members a pool can have.
+pub const MAX_POOL_MEMBERS: usize = 10;
+
+/// A whole payment in basis points (1/100 of a percent), the unit of the shares of a pool.
+pub const FULL_SHARE: u32 = 10_000;
+
+/// The `PoolMember` struct represents an instructor sharing the revenue of a pool.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolMember {
+  /// Instructor's account ID.
+  pub account_id: UserId,
+
+  /// Part of every payment to the pool credited to this member, in basis points. The shares of a pool sum to
+  /// `FULL_SHARE`
+  pub share: u32,
+
+  /// Credited yoctoNEAR the member has not withdrawn yet.
+  pub balance: U128,
+}
+
+/// The `PoolMetadata` struct represents a revenue pool of one or more instructors.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolMetadata {
+  /// Unique identifier for the pool, of type `PoolId`.
+  pub pool_id: PoolId,
+
+  /// Name of the pool.
+  pub name: String,
+
+  /// Instructor who created the pool.
+  pub creator_id: UserId,
+
+  /// Members of the pool and their shares.
+  pub members: Vec<PoolMember>,
+
+  /// Part of every sale of a course of the pool that goes to the pool, after the platform fee, in basis points.
+  pub sale_share: u32,
+
+  /// Everything the pool has received, in yoctoNEAR.
+  pub total_received: U128,
+
+  /// Date when the pool was created, represented as a timestamp.
+  pub created_at: u64,
+}
+
+/// The `PoolFeatures` trait defines the revenue pools of instructors.
+///
+/// A course that joins a pool sends `sale_share` basis points of every sale, after the platform fee, to the pool.
+/// The payment is split between the members by their shares and credited to them, and each member withdraws
+/// its balance when it wants.
+pub trait PoolFeatures {
+  /// Create a pool. Only an Instructor can call this function and every member must be an Instructor
+  fn create_pool(&mut self, name: String, members: Vec<(UserId, u32)>, sale_share: u32) -> PoolMetadata;
+
+  /// Make the sales of a course pay the pool, or stop it with None. Pools are paid in NEAR, so the course must
+  /// be priced in NEAR. Only the course owner, a member of the pool, can call this function
+  fn set_course_pool(&mut self, course_id: CourseId, pool_id: Option<PoolId>);
+
+  /// Withdraw `amount` (all by default) of the caller's balance in a pool. Requires exactly 1 yoctoNEAR
+  fn withdraw_from_pool(&mut self, pool_id: PoolId, amount: Option<U128>) -> U128;
+
+  /// Callback of `withdraw_from_pool`. Credit the amount back if the transfer failed
+  fn resolve_pool_withdraw(&mut self, pool_id: PoolId, account_id: UserId, amount: U128);
+
+  /// Get pool information
+  fn get_pool_metadata(&self, pool_id: PoolId) -> Option<PoolMetadata>;
+
+  /// Get the pools a user is member of. From 'start' to 'start + limit'
+  fn get_all_pools_per_user(&self, user_id: UserId, start: Option<u32>, limit: Option<u32>) -> Vec<PoolMetadata>;
+}
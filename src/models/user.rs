@@ -10,7 +10,7 @@ use super::course::CourseId;
 use super::skill::SkillId;
 
 /// The `Roles` enum represents the various roles a user can have within the system.
-#[derive(Deserialize, BorshDeserialize, BorshSerialize, Serialize, Default, Debug, PartialEq)]
+#[derive(Deserialize, BorshDeserialize, BorshSerialize, Serialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(crate = "near_sdk::serde")]
 pub enum Roles {
   /// The default role. Subscribers typically have access to consume content.
@@ -69,6 +69,12 @@ pub struct UserMetadata {
 
   /// Total number of courses owned by the user.
   pub courses_owned: u32,
+
+  /// Suspended users can not change their information, create, buy or grade courses
+  pub suspended: bool,
+
+  /// Manager who granted the Instructor role, if any. A manager only has power over the instructors it manages
+  pub managed_by: Option<UserId>,
 }
 
 /// The `JsonUser` struct provides a comprehensive view of a user in the system.
@@ -108,12 +114,6 @@ pub trait ImplUser {
     bio: Option<String>,
   );
 
-  /// Updates the role of a user and returns the updated user as a `JsonUser`.
-  fn update_role(&mut self) -> JsonUser;
-
-  /// Creates a pool request. The details of this method would be defined in its implementation.
-  fn create_pool();
-
   /// Returns a `JsonUser` representation of the user's metadata for the given user ID.
   fn get_user_metadata_by_user_id(&self, user_id: &UserId) -> Option<JsonUser>;
 
@@ -0,0 +1,47 @@
+//! Layout version of the `ELearningContract` state.
+//!
+//! The version lives under its own storage key next to the contract state and is written by `new`, so that
+//! a later layout change can tell which layout it reads.
+
+use near_sdk::{
+  borsh::{self, BorshDeserialize, BorshSerialize},
+  env, near_bindgen,
+  serde::{Deserialize, Serialize},
+};
+
+use super::contract::{ELearningContract, ELearningContractExt};
+
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+/// Layout versions of the e-learning contract state.
+#[derive(
+  BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StateVersion {
+  /// First deployed layout.
+  V1,
+}
+
+impl StateVersion {
+  pub const CURRENT: StateVersion = StateVersion::V1;
+
+  /// Version of the state in storage.
+  pub fn read() -> Self {
+    env::storage_read(STATE_VERSION_KEY)
+      .map(|bytes| Self::try_from_slice(&bytes).expect("Cannot deserialize the state version"))
+      .unwrap_or(StateVersion::V1)
+  }
+
+  pub fn write(self) {
+    env::storage_write(STATE_VERSION_KEY, &self.try_to_vec().unwrap());
+  }
+}
+
+#[near_bindgen]
+impl ELearningContract {
+  /// Layout version of the stored state
+  pub fn get_state_version(&self) -> StateVersion {
+    StateVersion::read()
+  }
+}
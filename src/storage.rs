@@ -0,0 +1,178 @@
+//! NEP-145 storage management shared by both contracts.
+//!
+//! Every call that writes to the contract state charges the caller for the bytes it added: first from the
+//! attached deposit, then from the storage balance the caller registered with `storage_deposit`. What is
+//! left of the attached deposit is refunded. A call that frees storage credits its cost back to the caller:
+//! to its storage balance when it is registered, or as a refund.
+
+use near_sdk::{
+  borsh::{self, BorshDeserialize, BorshSerialize},
+  collections::LookupMap,
+  env,
+  json_types::U128,
+  require,
+  serde::{Deserialize, Serialize},
+  AccountId, Balance, IntoStorageKey, Promise, StorageUsage,
+};
+
+/// Bytes an entry of the storage ledger takes: the longest account id, the balance and the trie overhead.
+pub const ACCOUNT_STORAGE_BYTES: StorageUsage = 200;
+
+/// The `StorageBalance` struct is the NEP-145 balance of an account.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+  /// Everything the account deposited and has not spent or withdrawn.
+  pub total: U128,
+
+  /// The part of `total` above the registration minimum, which pays for storage and can be withdrawn.
+  pub available: U128,
+}
+
+/// The `StorageBalanceBounds` struct is the NEP-145 range of accepted balances.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+  /// Deposit required to register an account.
+  pub min: U128,
+
+  /// There is no upper bound.
+  pub max: Option<U128>,
+}
+
+/// NEP-145 storage management functions.
+pub trait StorageManagement {
+  /// Register `account_id` (the caller by default) or add the attached deposit to its balance. With
+  /// `registration_only`, only the minimum is kept and the rest is refunded.
+  fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance;
+
+  /// Withdraw `amount` (everything available by default) of the caller's balance. Requires exactly 1 yoctoNEAR.
+  fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance;
+
+  fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
+
+  fn storage_balance_bounds(&self) -> StorageBalanceBounds;
+}
+
+/// Storage balances of the registered accounts.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct StorageLedger {
+  balances: LookupMap<AccountId, Balance>,
+}
+
+impl StorageLedger {
+  pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
+    Self { balances: LookupMap::new(prefix) }
+  }
+
+  /// Deposit required to register an account.
+  pub fn min_balance() -> Balance {
+    Balance::from(ACCOUNT_STORAGE_BYTES) * env::storage_byte_cost()
+  }
+
+  pub fn bounds() -> StorageBalanceBounds {
+    StorageBalanceBounds { min: U128(Self::min_balance()), max: None }
+  }
+
+  pub fn balance_of(&self, account_id: &AccountId) -> Option<StorageBalance> {
+    self.balances.get(account_id).map(|total| StorageBalance {
+      total: U128(total),
+      available: U128(total.saturating_sub(Self::min_balance())),
+    })
+  }
+
+  /// `storage_deposit` with the attached deposit.
+  pub fn deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+    let amount = env::attached_deposit();
+    let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+    let min_balance = Self::min_balance();
+
+    match self.balances.get(&account_id) {
+      Some(total) => {
+        if registration_only.unwrap_or(false) {
+          refund(amount);
+        } else {
+          self.balances.insert(&account_id, &(total + amount));
+        }
+      },
+      None => {
+        assert!(amount >= min_balance, "The attached deposit is less than the minimum storage balance");
+        if registration_only.unwrap_or(false) {
+          self.balances.insert(&account_id, &min_balance);
+          refund(amount - min_balance);
+        } else {
+          self.balances.insert(&account_id, &amount);
+        }
+      },
+    }
+
+    self.balance_of(&account_id).unwrap()
+  }
+
+  /// `storage_withdraw` for the caller.
+  pub fn withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+    assert_eq!(env::attached_deposit(), 1, "Requires attached deposit of exactly 1 yoctoNEAR");
+    let account_id = env::predecessor_account_id();
+    let balance = self.balance_of(&account_id).expect("The account is not registered");
+
+    let amount = amount.map(|amount| amount.0).unwrap_or(balance.available.0);
+    assert!(amount <= balance.available.0, "The amount is greater than the available storage balance");
+
+    self.balances.insert(&account_id, &(balance.total.0 - amount));
+    if amount > 0 {
+      Promise::new(account_id.clone()).transfer(amount);
+    }
+
+    self.balance_of(&account_id).unwrap()
+  }
+
+  /// Charge `account_id` for the bytes written since `initial_storage`, from `deposit` first and then from its
+  /// available balance, and refund what is left of `deposit`. The bytes freed since `initial_storage` are
+  /// credited to the storage balance of `account_id`, or sent to it when it is not registered.
+  pub fn charge(&mut self, account_id: &AccountId, initial_storage: StorageUsage, deposit: Balance) {
+    let current_storage = env::storage_usage();
+    if current_storage < initial_storage {
+      let freed = Balance::from(initial_storage - current_storage) * env::storage_byte_cost();
+      match self.balances.get(account_id) {
+        Some(total) => {
+          self.balances.insert(account_id, &(total + freed));
+        },
+        None => {
+          Promise::new(account_id.clone()).transfer(freed);
+        },
+      }
+      refund(deposit);
+      return;
+    }
+
+    let cost = Balance::from(current_storage - initial_storage) * env::storage_byte_cost();
+    let from_deposit = cost.min(deposit);
+    let from_balance = cost - from_deposit;
+
+    if from_balance > 0 {
+      let balance = self.balance_of(account_id);
+      require!(balance.is_some(), format!("Attach {} yoctoNEAR or call storage_deposit to pay for storage", cost));
+      let balance = balance.unwrap();
+      assert!(
+        balance.available.0 >= from_balance,
+        "Not enough storage balance: {} yoctoNEAR more is required",
+        from_balance - balance.available.0
+      );
+      self.balances.insert(account_id, &(balance.total.0 - from_balance));
+    }
+
+    refund(deposit - from_deposit);
+  }
+
+  /// `charge` the caller, who is the account every call acts for.
+  pub fn charge_caller(&mut self, initial_storage: StorageUsage, deposit: Balance) {
+    self.charge(&env::predecessor_account_id(), initial_storage, deposit);
+  }
+}
+
+/// Send back an unused part of the attached deposit to the caller.
+fn refund(amount: Balance) {
+  if amount > 0 {
+    Promise::new(env::predecessor_account_id()).transfer(amount);
+  }
+}
@@ -0,0 +1,110 @@
+//! Helpers shared by the unit tests of both contracts.
+
+use near_sdk::mock::VmAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, Balance};
+
+#[cfg(feature = "elearning")]
+use crate::models::{
+  contract::{ELearningContract, ELearningContractMetadata},
+  user::ImplUser,
+};
+#[cfg(feature = "copyright")]
+use crate::{content::ContentRef, Contract, Copyright, Work};
+#[cfg(feature = "copyright")]
+use near_sdk::json_types::U128;
+#[cfg(any(feature = "copyright", feature = "elearning"))]
+use near_sdk::ONE_NEAR;
+
+/// Call the contract as `account_id`, attaching `attached_deposit`.
+pub(crate) fn set_caller(account_id: AccountId, attached_deposit: Balance) {
+  set_caller_at(account_id, attached_deposit, 0);
+}
+
+/// Call the contract as `account_id` at a block time in milliseconds.
+pub(crate) fn set_caller_at(account_id: AccountId, attached_deposit: Balance, block_timestamp_ms: u64) {
+  let mut context = VMContextBuilder::new();
+  context
+    .current_account_id(accounts(0))
+    .signer_account_id(account_id.clone())
+    .predecessor_account_id(account_id)
+    .attached_deposit(attached_deposit)
+    .block_timestamp(block_timestamp_ms * 1_000_000);
+  testing_env!(context.build());
+}
+
+/// Call the contract as `predecessor_id` in a transaction signed by `signer_id`, as a token contract does.
+pub(crate) fn set_signer_and_caller(signer_id: AccountId, predecessor_id: AccountId, attached_deposit: Balance) {
+  let mut context = VMContextBuilder::new();
+  context
+    .current_account_id(accounts(0))
+    .signer_account_id(signer_id)
+    .predecessor_account_id(predecessor_id)
+    .attached_deposit(attached_deposit);
+  testing_env!(context.build());
+}
+
+/// NEAR sent by the contract, by receiver.
+pub(crate) fn transferred_deposits() -> Vec<(AccountId, Balance)> {
+  get_created_receipts()
+    .into_iter()
+    .flat_map(|receipt| {
+      receipt.actions.into_iter().filter_map(move |action| match action {
+        VmAction::Transfer { deposit } => Some((receipt.receiver_id.clone(), deposit)),
+        _ => None,
+      })
+    })
+    .collect()
+}
+
+/// Reference to `bytes` stored at `uri`.
+#[cfg(feature = "copyright")]
+pub(crate) fn content_ref(uri: &str, bytes: &[u8]) -> ContentRef {
+  ContentRef {
+    uri: uri.to_string(),
+    hash: content_crypto::hash_content(bytes).into(),
+    size: bytes.len() as u64,
+    mime: "application/octet-stream".to_string(),
+  }
+}
+
+/// accounts(1) registers as the author Alice and writes the work "Song", inviting `collaborators`.
+#[cfg(feature = "copyright")]
+pub(crate) fn create_song(
+  contract: &mut Contract,
+  collaborators: Option<Vec<AccountId>>,
+  fee: Option<U128>,
+  ratios: Option<Vec<(AccountId, u32)>>,
+) -> Work {
+  set_caller(accounts(1), ONE_NEAR);
+  contract.create_author("Alice".to_string(), 30);
+  let content = content_ref("ipfs://bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku", b"song");
+  contract.create_work("Song".to_string(), content, collaborators, fee, ratios, [2; 32].into())
+}
+
+#[cfg(feature = "elearning")]
+pub(crate) fn contract_metadata() -> ELearningContractMetadata {
+  ELearningContractMetadata {
+    spec: "elearning-1.0.0".to_string(),
+    name: "E-Learning".to_string(),
+    symbol: "EDU".to_string(),
+    icon: None,
+    base_uri: None,
+    reference: None,
+    reference_hash: None,
+  }
+}
+
+/// E-learning contract owned by accounts(0).
+#[cfg(feature = "elearning")]
+pub(crate) fn new_contract() -> ELearningContract {
+  set_caller(accounts(0), ONE_NEAR);
+  ELearningContract::new(accounts(0), contract_metadata())
+}
+
+/// `account_id` subscribes, paying for its storage with the attached deposit.
+#[cfg(feature = "elearning")]
+pub(crate) fn create_user(contract: &mut ELearningContract, account_id: AccountId) {
+  set_caller(account_id, ONE_NEAR);
+  contract.create_user(None, None, None, None);
+}